@@ -2,38 +2,80 @@ use specta_typescript::BigIntExportBehavior;
 use tauri_specta::{Builder, collect_commands};
 
 use crate::commands::alerts::{self, GetAlertsResponse};
-use crate::commands::builds::GetBuildsResponse;
+use crate::commands::builds::{GetBuildsResponse, QueryBuildsResponse};
 use crate::path;
+use crate::services::quickbuild::QuickBuildErrorKind;
 
-use crate::commands::{app, builds, settings, windows};
+use crate::commands::{
+    app, build_requests, builds, notifications, polling, quiet_hours, rules, settings, watch_list,
+    windows,
+};
 use crate::types::alert::{Alert, AlertCategory, AlertPriority};
-use crate::types::build::Build;
+use crate::types::build::{
+    Build, BuildQuery, BuildStatus, BuildTransition, SortOrder, TransitionKind,
+};
+use crate::types::notification::{NotificationAction, NotificationTarget};
+use crate::types::server::ConnectionDiagnostic;
 use crate::types::settings::AppSettings;
+use crate::types::watch::ConfigurationStatus;
 
 pub fn generate_bindings() -> Builder<tauri::Wry> {
     Builder::<tauri::Wry>::new()
         .commands(collect_commands![
             settings::load_settings,
             settings::save_settings,
+            settings::test_connection,
             // notifications::send_native_notification,
+            notifications::handle_notification_action,
             path::get_config_dir,
             path::get_logs_dir,
             windows::show_dashboard_window,
+            windows::show_build_in_dashboard,
             windows::close_dashboard_window,
             windows::show_main_window,
             windows::close_main_window,
             app::get_app_info,
             builds::get_builds,
+            builds::query_builds,
+            builds::mark_build_read,
+            builds::mark_all_builds_read,
+            build_requests::request_build,
+            build_requests::rebuild,
+            build_requests::cancel_build,
             alerts::get_alerts,
+            alerts::acknowledge_alert,
+            alerts::acknowledge_all_alerts,
+            alerts::mark_alert_read,
+            alerts::mark_all_alerts_read,
+            rules::get_notification_rules,
+            rules::save_notification_rules,
+            quiet_hours::get_quiet_hours,
+            quiet_hours::save_quiet_hours,
+            watch_list::add_watched_configuration,
+            watch_list::remove_watched_configuration,
+            watch_list::set_watch_list_options,
+            watch_list::get_configuration_statuses,
+            polling::poll_now,
         ])
         .error_handling(tauri_specta::ErrorHandlingMode::Throw)
         .typ::<AppSettings>()
         .typ::<Build>()
         .typ::<GetBuildsResponse>()
+        .typ::<BuildQuery>()
+        .typ::<BuildStatus>()
+        .typ::<SortOrder>()
+        .typ::<BuildTransition>()
+        .typ::<TransitionKind>()
+        .typ::<QueryBuildsResponse>()
         .typ::<Alert>()
         .typ::<AlertPriority>()
         .typ::<AlertCategory>()
         .typ::<GetAlertsResponse>()
+        .typ::<QuickBuildErrorKind>()
+        .typ::<NotificationAction>()
+        .typ::<NotificationTarget>()
+        .typ::<ConfigurationStatus>()
+        .typ::<ConnectionDiagnostic>()
 }
 
 /// Export TypeScript bindings to the frontend.
@@ -2,22 +2,33 @@ use std::sync::Mutex;
 
 use serde::Serialize;
 use specta::Type;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
 use time::OffsetDateTime;
 
-use crate::{AppState, types::alert::Alert};
+use crate::{
+    AppState,
+    commands::build_requests::create_client,
+    services::{database::to_millis, quickbuild::QuickBuildErrorKind},
+    tray,
+    types::{alert::Alert, server::ServerStatus},
+};
 
 #[derive(Serialize, Type, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GetAlertsResponse {
     pub alerts: Vec<Alert>,
     pub error: Option<String>,
+    pub error_kind: Option<QuickBuildErrorKind>,
     #[serde(
         default,
         skip_serializing_if = "Option::is_none",
         with = "crate::serde::option_four_year_iso8601"
     )]
     pub last_polling_time: Option<OffsetDateTime>,
+    /// Number of alerts of the history not marked as read
+    pub unread_count: u32,
+    /// Polling status of each server, the error above is the one of the first failing server
+    pub servers: Vec<ServerStatus>,
 }
 
 #[tauri::command]
@@ -28,10 +39,117 @@ pub async fn get_alerts(state: State<'_, Mutex<AppState>>) -> Result<GetAlertsRe
         .map_err(|e| format!("Failed to acquire lock for getting alerts: {e}"))?;
 
     let alerts = state_guard.get_alerts();
+    let servers = state_guard.get_server_statuses(|server| server.alert_polling_error.as_ref());
+    let failing_server = servers.iter().find(|server| server.error.is_some());
 
     Ok(GetAlertsResponse {
         alerts,
-        error: state_guard.alert_polling_error.clone(),
-        last_polling_time: state_guard.last_polling_time,
+        error: failing_server.and_then(|server| server.error.clone()),
+        error_kind: failing_server.and_then(|server| server.error_kind),
+        last_polling_time: state_guard.get_last_polling_time(),
+        unread_count: state_guard.get_unread_alerts_count(),
+        servers,
     })
 }
+
+/// Marks an alert of the history as read or unread
+#[tauri::command]
+#[specta::specta]
+pub async fn mark_alert_read(
+    app: AppHandle,
+    server: String,
+    alert_id: i64,
+    read: bool,
+) -> Result<(), String> {
+    app.state::<Mutex<AppState>>()
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock for marking alert as read: {e}"))?
+        .set_alert_read(&server, alert_id, read)?;
+
+    tray::refresh_tray_status(&app);
+    let _ = app.emit("alerts-refresh-page", ());
+    Ok(())
+}
+
+/// Marks every alert of the history as read, returns how many alerts were unread
+#[tauri::command]
+#[specta::specta]
+pub async fn mark_all_alerts_read(app: AppHandle) -> Result<u32, String> {
+    let count = app
+        .state::<Mutex<AppState>>()
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock for marking alerts as read: {e}"))?
+        .mark_all_alerts_read()?;
+
+    tray::refresh_tray_status(&app);
+    let _ = app.emit("alerts-refresh-page", ());
+    Ok(count as u32)
+}
+
+/// Acknowledges the alert on the QuickBuild server, then in the local alert history
+#[tauri::command]
+#[specta::specta]
+pub async fn acknowledge_alert(
+    app: AppHandle,
+    server: String,
+    alert_id: i64,
+) -> Result<(), String> {
+    create_client(&app, &server)?
+        .acknowledge_alert(alert_id)
+        .await
+        .map_err(|e| format!("Failed to acknowledge alert {alert_id}: {e}"))?;
+
+    {
+        let state = app.state::<Mutex<AppState>>();
+        let mut state_guard = state
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for acknowledging alert: {e}"))?;
+        state_guard.acknowledge_alert(&server, alert_id, to_millis(OffsetDateTime::now_utc()))?;
+    }
+
+    tray::refresh_tray_status(&app);
+    let _ = app.emit("alerts-refresh-page", ());
+    Ok(())
+}
+
+/// Outcome of acknowledging several alerts, the alerts failing to be acknowledged do not stop
+/// the others
+#[derive(Serialize, Type, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AcknowledgeAlertsResult {
+    /// Number of alerts acknowledged
+    pub acknowledged: u32,
+    /// Errors of the alerts failing to be acknowledged
+    pub errors: Vec<String>,
+}
+
+/// Acknowledges every alert of the local history not acknowledged yet, returns how many
+/// alerts were acknowledged along with the errors of the alerts that failed
+#[tauri::command]
+#[specta::specta]
+pub async fn acknowledge_all_alerts(app: AppHandle) -> Result<AcknowledgeAlertsResult, String> {
+    let alert_ids: Vec<(String, i64)> = {
+        let state = app.state::<Mutex<AppState>>();
+        let state_guard = state
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for getting alerts: {e}"))?;
+        state_guard
+            .get_alerts()
+            .iter()
+            .filter(|alert| !alert.fixed && !alert.is_acknowledged())
+            .map(|alert| (alert.server.clone(), alert.id))
+            .collect()
+    };
+
+    let mut result = AcknowledgeAlertsResult::default();
+    for (server, alert_id) in alert_ids {
+        match acknowledge_alert(app.clone(), server, alert_id).await {
+            Ok(()) => result.acknowledged += 1,
+            Err(e) => {
+                tracing::warn!("{e}");
+                result.errors.push(e);
+            }
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,60 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{AppState, services::quickbuild::QuickBuildClient};
+
+/// Client of the named QuickBuild server, for the operations requested by the user
+pub fn create_client(app: &AppHandle, server: &str) -> Result<QuickBuildClient, String> {
+    let state = app.state::<Mutex<AppState>>();
+    let state_guard = state
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock for getting settings: {e}"))?;
+    if !state_guard.settings.is_configured() {
+        return Err("QuickBuild server is not configured".to_string());
+    }
+    Ok(QuickBuildClient::from_profile(
+        &state_guard.settings.get_server(server)?,
+    ))
+}
+
+/// Requests a new build of the configuration, returns the id of the build request
+#[tauri::command]
+#[specta::specta]
+pub async fn request_build(
+    app: AppHandle,
+    server: String,
+    configuration_id: i64,
+    variables: Option<BTreeMap<String, String>>,
+) -> Result<String, String> {
+    create_client(&app, &server)?
+        .request_build(configuration_id, &variables.unwrap_or_default())
+        .await
+        .map_err(|e| format!("Failed to request build: {e}"))
+}
+
+/// Requests a new build of the configuration of the given build, with the variable values the
+/// build was requested with, returns the id of the build request
+#[tauri::command]
+#[specta::specta]
+pub async fn rebuild(
+    app: AppHandle,
+    server: String,
+    build_id: i64,
+    configuration_id: i64,
+) -> Result<String, String> {
+    create_client(&app, &server)?
+        .rebuild(build_id, configuration_id)
+        .await
+        .map_err(|e| format!("Failed to rebuild build {build_id}: {e}"))
+}
+
+/// Stops a running build, or removes it from the queue when it is still waiting
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_build(app: AppHandle, server: String, build_id: i64) -> Result<(), String> {
+    create_client(&app, &server)?
+        .cancel_build(build_id)
+        .await
+        .map_err(|e| format!("Failed to cancel build {build_id}: {e}"))
+}
@@ -2,22 +2,44 @@ use std::sync::Mutex;
 
 use serde::Serialize;
 use specta::Type;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
 use time::OffsetDateTime;
 
-use crate::{AppState, types::build::Build};
+use crate::{
+    AppState,
+    services::quickbuild::QuickBuildErrorKind,
+    tray,
+    types::{
+        build::{Build, BuildQuery},
+        server::ServerStatus,
+    },
+};
 
 #[derive(Serialize, Type, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GetBuildsResponse {
     pub builds: Vec<Build>,
     pub error: Option<String>,
+    pub error_kind: Option<QuickBuildErrorKind>,
     #[serde(
         default,
         skip_serializing_if = "Option::is_none",
         with = "crate::serde::option_four_year_iso8601"
     )]
     pub last_polling_time: Option<OffsetDateTime>,
+    /// Number of polls in a row that failed, 0 when the last poll succeeded
+    pub consecutive_failures: u32,
+    /// When the poller retries after failures, only set while polling is failing
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde::option_four_year_iso8601"
+    )]
+    pub next_retry_time: Option<OffsetDateTime>,
+    /// Number of builds of the history not marked as read
+    pub unread_count: u32,
+    /// Polling status of each server, the error above is the one of the first failing server
+    pub servers: Vec<ServerStatus>,
 }
 
 #[tauri::command]
@@ -28,10 +50,82 @@ pub async fn get_builds(state: State<'_, Mutex<AppState>>) -> Result<GetBuildsRe
         .map_err(|e| format!("Failed to acquire lock for getting builds: {e}"))?;
 
     let builds = state_guard.get_builds();
+    let servers = state_guard.get_server_statuses(|server| server.build_polling_error.as_ref());
+    let failing_server = servers.iter().find(|server| server.error.is_some());
 
     Ok(GetBuildsResponse {
         builds,
-        error: state_guard.build_polling_error.clone(),
-        last_polling_time: state_guard.last_polling_time,
+        error: failing_server.and_then(|server| server.error.clone()),
+        error_kind: failing_server.and_then(|server| server.error_kind),
+        last_polling_time: state_guard.get_last_polling_time(),
+        consecutive_failures: servers
+            .iter()
+            .map(|server| server.consecutive_failures)
+            .max()
+            .unwrap_or(0),
+        next_retry_time: servers
+            .iter()
+            .filter_map(|server| server.next_retry_time)
+            .min(),
+        unread_count: state_guard.get_unread_builds_count(),
+        servers,
     })
 }
+
+/// Marks a build of the history as read or unread
+#[tauri::command]
+#[specta::specta]
+pub async fn mark_build_read(
+    app: AppHandle,
+    server: String,
+    build_id: i64,
+    read: bool,
+) -> Result<(), String> {
+    app.state::<Mutex<AppState>>()
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock for marking build as read: {e}"))?
+        .set_build_read(&server, build_id, read)?;
+
+    tray::refresh_tray_status(&app);
+    let _ = app.emit("builds-refresh-page", ());
+    Ok(())
+}
+
+/// Marks every build of the history as read, returns how many builds were unread
+#[tauri::command]
+#[specta::specta]
+pub async fn mark_all_builds_read(app: AppHandle) -> Result<u32, String> {
+    let count = app
+        .state::<Mutex<AppState>>()
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock for marking builds as read: {e}"))?
+        .mark_all_builds_read()?;
+
+    tray::refresh_tray_status(&app);
+    let _ = app.emit("builds-refresh-page", ());
+    Ok(count as u32)
+}
+
+#[derive(Serialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryBuildsResponse {
+    pub builds: Vec<Build>,
+    /// Number of builds matching the filters, regardless of offset and limit
+    pub total: u32,
+}
+
+/// Searches the build history with filters and pagination
+#[tauri::command]
+#[specta::specta]
+pub async fn query_builds(
+    state: State<'_, Mutex<AppState>>,
+    query: BuildQuery,
+) -> Result<QueryBuildsResponse, String> {
+    tracing::debug!("Querying builds: {query:?}");
+    let state_guard = state
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock for querying builds: {e}"))?;
+
+    let (builds, total) = state_guard.query_builds(&query)?;
+    Ok(QueryBuildsResponse { builds, total })
+}
@@ -1,6 +1,11 @@
 pub mod alerts;
 pub mod app;
+pub mod build_requests;
 pub mod builds;
 pub mod notifications;
+pub mod polling;
+pub mod quiet_hours;
+pub mod rules;
 pub mod settings;
+pub mod watch_list;
 pub mod windows;
@@ -1,11 +1,24 @@
 use std::sync::Mutex;
 
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_notification::NotificationExt;
+use time::OffsetDateTime;
 
-use crate::AppState;
+use crate::{
+    AppState,
+    commands::{
+        alerts::acknowledge_alert,
+        build_requests::create_client,
+        windows::{show_build_in_dashboard, show_main_window},
+    },
+    constants::{DEFAULT_SERVER_NAME, MAX_DIGEST_LINES},
+    types::{
+        notification::{NotificationAction, NotificationTarget},
+        quiet_hours::QuietHoursMode,
+    },
+};
 
-/// Sends a native system notification.
+/// Sends a native system notification, with the actions of its target if any.
 /// On mobile platforms, returns an error as notifications are not yet supported.
 // #[tauri::command]
 // #[specta::specta]
@@ -13,25 +26,122 @@ pub async fn send_native_notification(
     app: AppHandle,
     title: String,
     body: Option<String>,
+    target: Option<NotificationTarget>,
 ) -> Result<(), String> {
     {
         let state = app.state::<Mutex<AppState>>();
-        let settings = &state.lock().unwrap().settings;
+        let mut state = state.lock().unwrap();
+        let settings = &state.settings;
         let enable_notifications = settings.enable_notifications;
         if !enable_notifications {
             tracing::info!("Notifications are disabled");
             return Ok(());
         }
+
+        let quiet_hours_mode = settings.quiet_hours.mode;
+        if settings.quiet_hours.is_quiet(OffsetDateTime::now_utc()) {
+            match quiet_hours_mode {
+                QuietHoursMode::Suppress => {
+                    tracing::info!("Suppressing notification during quiet hours: {title}");
+                }
+                QuietHoursMode::Digest => {
+                    tracing::info!("Holding notification until quiet hours end: {title}");
+                    state.quiet_notifications.push(title);
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    show_notification(&app, &title, body, target)
+}
+
+/// Delivers the notifications held back during quiet hours as a single digest, once quiet
+/// hours are over
+pub fn send_quiet_hours_digest(app: &AppHandle) -> Result<(), String> {
+    let titles = {
+        let state = app.state::<Mutex<AppState>>();
+        let mut state = state.lock().unwrap();
+        if state.quiet_notifications.is_empty()
+            || state
+                .settings
+                .quiet_hours
+                .is_quiet(OffsetDateTime::now_utc())
+        {
+            return Ok(());
+        }
+        std::mem::take(&mut state.quiet_notifications)
+    };
+
+    tracing::info!(
+        "Sending digest of {} quiet hours notifications",
+        titles.len()
+    );
+    let title = match titles.len() {
+        1 => "1 notification during quiet hours".to_string(),
+        len => format!("{len} notifications during quiet hours"),
+    };
+    let mut body = titles
+        .iter()
+        .take(MAX_DIGEST_LINES)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if titles.len() > MAX_DIGEST_LINES {
+        body.push_str(&format!(
+            "\n and {} more ...",
+            titles.len() - MAX_DIGEST_LINES
+        ));
     }
+    show_notification(app, &title, Some(body), None)
+}
 
+fn show_notification(
+    app: &AppHandle,
+    title: &str,
+    body: Option<String>,
+    target: Option<NotificationTarget>,
+) -> Result<(), String> {
     tracing::info!("Sending native notification: {title}");
 
-    let mut builder = app.notification().builder().title(&title);
+    // the notification plugin offers no actions on desktop
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    let target = match target {
+        Some(target) if target.build_id.is_some() || target.alert_id.is_some() => {
+            return crate::services::desktop_notifications::show_notification(
+                app, title, body, target,
+            );
+        }
+        target => target,
+    };
+
+    let mut builder = app.notification().builder().title(title);
 
     if let Some(body_text) = body {
         builder = builder.body(body_text);
     }
 
+    // the target comes back in the extra payload when an action is performed
+    if let Some(target) = target {
+        if let Some(server) = target.server {
+            builder = builder.extra("server", server);
+        }
+        #[cfg(mobile)]
+        if let Some(action_type_id) = target.action_type_id() {
+            builder = builder.action_type_id(action_type_id);
+        }
+        // ids are strings, as declared by the bindings
+        if let Some(build_id) = target.build_id {
+            builder = builder.extra("buildId", build_id.to_string());
+        }
+        if let Some(configuration_id) = target.configuration_id {
+            builder = builder.extra("configurationId", configuration_id.to_string());
+        }
+        if let Some(alert_id) = target.alert_id {
+            builder = builder.extra("alertId", alert_id.to_string());
+        }
+    }
+
     match builder.show() {
         Ok(_) => {
             tracing::debug!("Native notification sent successfully");
@@ -43,3 +153,63 @@ pub async fn send_native_notification(
         }
     }
 }
+
+/// Handles the actions performed on the notifications, forwarded by the frontend on mobile
+/// platforms and by the desktop notifications otherwise
+#[tauri::command]
+#[specta::specta]
+pub async fn handle_notification_action(
+    app: AppHandle,
+    action: NotificationAction,
+    target: NotificationTarget,
+) -> Result<(), String> {
+    tracing::info!("Handling notification action {action:?} on {target:?}");
+    let server = target
+        .server
+        .clone()
+        .unwrap_or_else(|| DEFAULT_SERVER_NAME.to_string());
+
+    match (action, target) {
+        (
+            NotificationAction::Open | NotificationAction::OpenBuild,
+            NotificationTarget {
+                build_id: Some(build_id),
+                ..
+            },
+        ) => show_build_in_dashboard(app, server, build_id),
+        (
+            NotificationAction::Open,
+            NotificationTarget {
+                alert_id: Some(_), ..
+            },
+        ) => {
+            let _ = app.emit("menu-view-alerts", ());
+            show_main_window(app, Some("Alerts"))
+        }
+        (
+            NotificationAction::Rebuild,
+            NotificationTarget {
+                build_id: Some(build_id),
+                configuration_id: Some(configuration_id),
+                ..
+            },
+        ) => {
+            let client = create_client(&app, &server)?;
+            client
+                .rebuild(build_id, configuration_id)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Failed to rebuild build {build_id}: {e}"))
+        }
+        (
+            NotificationAction::Acknowledge,
+            NotificationTarget {
+                alert_id: Some(alert_id),
+                ..
+            },
+        ) => acknowledge_alert(app, server, alert_id).await,
+        (action, target) => Err(format!(
+            "Notification action {action:?} is not applicable to {target:?}"
+        )),
+    }
+}
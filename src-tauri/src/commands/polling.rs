@@ -0,0 +1,11 @@
+use tauri::AppHandle;
+
+use crate::{services::poll, types::server::ServerStatus};
+
+/// Polls the servers right away instead of waiting for the next poll, returns their status
+/// once they are polled
+#[tauri::command]
+#[specta::specta]
+pub async fn poll_now(app: AppHandle) -> Result<Vec<ServerStatus>, String> {
+    poll::poll_now(&app).await
+}
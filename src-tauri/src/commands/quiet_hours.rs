@@ -0,0 +1,39 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager, State, Wry};
+
+use crate::{
+    AppState,
+    types::{quiet_hours::QuietHours, settings::AppSettings},
+};
+
+/// Quiet hours of the settings
+#[tauri::command]
+#[specta::specta]
+pub fn get_quiet_hours(state: State<'_, Mutex<AppState>>) -> Result<QuietHours, String> {
+    let state_guard = state
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock for getting quiet hours: {e}"))?;
+    Ok(state_guard.settings.quiet_hours.clone())
+}
+
+/// Replaces the quiet hours, nothing is saved when a period has an invalid time. The
+/// notifications held back for the digest are only kept in memory and lost when the app quits.
+#[tauri::command]
+#[specta::specta]
+pub fn save_quiet_hours(app: AppHandle<Wry>, quiet_hours: QuietHours) -> Result<(), String> {
+    tracing::info!("Saving quiet hours ...");
+    quiet_hours.validate()?;
+
+    AppSettings::update(&app, |settings| settings.quiet_hours = quiet_hours)?;
+    {
+        let state = app.state::<Mutex<AppState>>();
+        let mut state_guard = state
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for reloading settings: {e}"))?;
+        state_guard.reload_settings(&app)?;
+    }
+
+    let _ = app.emit("quiet-hours-changed", ());
+    Ok(())
+}
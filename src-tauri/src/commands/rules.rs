@@ -0,0 +1,46 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager, State, Wry};
+
+use crate::{
+    AppState,
+    services::rules::validate_rule,
+    types::{rule::NotificationRule, settings::AppSettings},
+};
+
+/// Notification rules of the settings, in the order they are evaluated
+#[tauri::command]
+#[specta::specta]
+pub fn get_notification_rules(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<NotificationRule>, String> {
+    let state_guard = state
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock for getting notification rules: {e}"))?;
+    Ok(state_guard.settings.notification_rules.clone())
+}
+
+/// Replaces the notification rules, nothing is saved when a rule is invalid
+#[tauri::command]
+#[specta::specta]
+pub fn save_notification_rules(
+    app: AppHandle<Wry>,
+    rules: Vec<NotificationRule>,
+) -> Result<(), String> {
+    tracing::info!("Saving {} notification rules", rules.len());
+    for rule in &rules {
+        validate_rule(rule)?;
+    }
+
+    AppSettings::update(&app, |settings| settings.notification_rules = rules)?;
+    {
+        let state = app.state::<Mutex<AppState>>();
+        let mut state_guard = state
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for reloading settings: {e}"))?;
+        state_guard.reload_settings(&app)?;
+    }
+
+    let _ = app.emit("notification-rules-changed", ());
+    Ok(())
+}
@@ -1,24 +1,106 @@
 use std::sync::Mutex;
 
-use tauri::{AppHandle, Manager, State, Wry};
+use tauri::{AppHandle, Emitter, Manager, State, Url, Wry};
+use time::OffsetDateTime;
 
-use crate::{AppState, constants::DASHBOARD_WINDOW_NAME, types::settings::AppSettings};
+use crate::{
+    AppState,
+    constants::{DASHBOARD_WINDOW_NAME, REDACTED_TOKEN},
+    services::{credentials::CredentialStore, poll, quickbuild::QuickBuildClient},
+    tray,
+    types::{
+        server::{ConnectionDiagnostic, ServerProfile},
+        settings::AppSettings,
+    },
+};
 
+/// Settings of the app, with the access tokens redacted
 #[tauri::command]
 #[specta::specta]
 pub fn load_settings(state: State<'_, Mutex<AppState>>) -> Result<AppSettings, String> {
     tracing::info!("Loading app settings ...");
-    let settings = state.lock().unwrap().settings.clone();
+    let settings = state.lock().unwrap().settings.redacted();
     Ok(settings)
 }
 
+/// Saves the settings, the servers saved are kept when the settings have none, as sent by the
+/// forms not editing them. The settings edited by their own commands are kept as saved.
 #[tauri::command]
 #[specta::specta]
-pub fn save_settings(app: AppHandle<Wry>, settings: AppSettings) -> Result<(), String> {
+pub fn save_settings(app: AppHandle<Wry>, mut settings: AppSettings) -> Result<(), String> {
     tracing::info!("Saving app settings ...");
+    {
+        let state = app.state::<Mutex<AppState>>();
+        let state_guard = state
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for getting settings: {e}"))?;
+        if settings.servers.is_empty() {
+            settings.servers = state_guard.settings.redacted().servers;
+        }
+        settings.keep_saved_fields(&state_guard.settings);
+    }
     settings.save(&app)?;
     let win = app.get_webview_window(DASHBOARD_WINDOW_NAME).unwrap();
     let _ = win.navigate(settings.get_dashboard_url());
     let state = app.state::<Mutex<AppState>>();
-    state.lock().unwrap().reload_settings(&app)
+    state.lock().unwrap().reload_settings(&app)?;
+    poll::wake_up(&app);
+    tray::refresh_tray_menu(&app);
+    tray::refresh_tray_status(&app);
+    Ok(())
+}
+
+/// Checks the server URL, the credentials and the version of the server before the settings
+/// are saved. A redacted token stands for the saved token of the server.
+#[tauri::command]
+#[specta::specta]
+pub async fn test_connection(
+    app: AppHandle<Wry>,
+    mut server: ServerProfile,
+) -> Result<ConnectionDiagnostic, String> {
+    tracing::info!("Testing connection to server {}", server.name);
+    server.server_url = server.server_url.trim().trim_end_matches('/').to_string();
+    Url::parse(&server.server_url)
+        .map_err(|e| format!("Invalid server URL {}: {e}", server.server_url))?;
+
+    // the saved token is looked up by name, the server may be disabled or not saved yet with
+    // its other fields
+    if server.token == REDACTED_TOKEN {
+        server.token = app
+            .state::<CredentialStore>()
+            .get_token(&server.name)?
+            .ok_or_else(|| format!("No token is saved for server {}", server.name))?;
+    }
+
+    Ok(QuickBuildClient::from_profile(&server)
+        .test_connection()
+        .await)
+}
+
+/// Pauses or resumes polling, optionally until the given time, and notifies the tray and
+/// the frontend about the change
+pub fn set_paused(
+    app: &AppHandle<Wry>,
+    paused: bool,
+    paused_until: Option<OffsetDateTime>,
+) -> Result<(), String> {
+    tracing::info!("Setting polling paused: {paused}, until: {paused_until:?}");
+    AppSettings::update(app, |settings| {
+        settings.paused = paused;
+        settings.paused_until = paused_until.filter(|_| paused);
+    })?;
+
+    {
+        let state = app.state::<Mutex<AppState>>();
+        let mut state_guard = state
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for reloading settings: {e}"))?;
+        state_guard.reload_settings(app)?;
+    }
+
+    poll::wake_up(app);
+    tray::refresh_tray_menu(app);
+    tray::refresh_tray_status(app);
+    let _ = app.emit("polling-paused-changed", paused);
+    Ok(())
 }
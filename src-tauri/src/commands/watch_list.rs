@@ -0,0 +1,117 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager, State, Wry};
+
+use crate::{
+    AppState,
+    services::{poll, rules::compile_path_pattern},
+    tray,
+    types::{
+        settings::AppSettings,
+        watch::{ConfigurationStatus, WatchList, WatchedConfiguration},
+    },
+};
+
+/// Adds a configuration to the watch list, returns whether it was not watched yet
+#[tauri::command]
+#[specta::specta]
+pub fn add_watched_configuration(
+    app: AppHandle<Wry>,
+    configuration: WatchedConfiguration,
+) -> Result<bool, String> {
+    tracing::info!("Adding {configuration:?} to the watch list");
+    if let WatchedConfiguration::Path { path: pattern } = &configuration {
+        compile_path_pattern(pattern)?;
+    }
+
+    update_watch_list(&app, |watch_list| {
+        if watch_list.configurations.contains(&configuration) {
+            return false;
+        }
+        watch_list.configurations.push(configuration);
+        true
+    })
+}
+
+/// Removes a configuration from the watch list, returns whether it was watched
+#[tauri::command]
+#[specta::specta]
+pub fn remove_watched_configuration(
+    app: AppHandle<Wry>,
+    configuration: WatchedConfiguration,
+) -> Result<bool, String> {
+    tracing::info!("Removing {configuration:?} from the watch list");
+    update_watch_list(&app, |watch_list| {
+        let len = watch_list.configurations.len();
+        watch_list
+            .configurations
+            .retain(|watched| watched != &configuration);
+        watch_list.configurations.len() != len
+    })
+}
+
+/// Sets whether only the watched configurations are notified and decide the tray status
+#[tauri::command]
+#[specta::specta]
+pub fn set_watch_list_options(
+    app: AppHandle<Wry>,
+    notify_only_watched: bool,
+    status_only_watched: bool,
+) -> Result<(), String> {
+    tracing::info!(
+        "Setting watch list options, notify only watched: {notify_only_watched}, status only \
+        watched: {status_only_watched}"
+    );
+    update_watch_list(&app, |watch_list| {
+        let changed = watch_list.notify_only_watched != notify_only_watched
+            || watch_list.status_only_watched != status_only_watched;
+        watch_list.notify_only_watched = notify_only_watched;
+        watch_list.status_only_watched = status_only_watched;
+        changed
+    })?;
+    Ok(())
+}
+
+/// Current status of the watched configurations, from their latest build on the server
+#[tauri::command]
+#[specta::specta]
+pub fn get_configuration_statuses(
+    state: State<'_, Mutex<AppState>>,
+) -> Result<Vec<ConfigurationStatus>, String> {
+    let state_guard = state
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock for getting configuration statuses: {e}"))?;
+    Ok(state_guard
+        .configuration_statuses
+        .values()
+        .cloned()
+        .collect())
+}
+
+/// Saves the changed watch list, and notifies the tray and the frontend about the change
+fn update_watch_list(
+    app: &AppHandle<Wry>,
+    update: impl FnOnce(&mut WatchList) -> bool,
+) -> Result<bool, String> {
+    let mut changed = false;
+    AppSettings::update(app, |settings| {
+        changed = update(&mut settings.watch_list);
+    })?;
+    if !changed {
+        return Ok(false);
+    }
+
+    {
+        let state = app.state::<Mutex<AppState>>();
+        let mut state_guard = state
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for reloading settings: {e}"))?;
+        state_guard.reload_settings(app)?;
+    }
+
+    // the statuses of newly watched configurations are fetched right away
+    poll::wake_up(app);
+    tray::refresh_tray_status(app);
+    let _ = app.emit("watch-list-changed", ());
+    Ok(true)
+}
@@ -1,9 +1,14 @@
+use std::sync::Mutex;
+
 use tauri::Manager;
 #[cfg(target_os = "linux")]
 use tauri::WebviewWindow;
 // use tauri_plugin_positioner::{Position, WindowExt};
 
-use crate::constants::{DASHBOARD_WINDOW_NAME, MAIN_WINDOW_NAME};
+use crate::{
+    AppState,
+    constants::{DASHBOARD_WINDOW_NAME, MAIN_WINDOW_NAME},
+};
 
 #[tauri::command]
 #[specta::specta]
@@ -27,6 +32,32 @@ pub fn show_dashboard_window(app: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Opens the given build of the QuickBuild server in the dashboard window
+#[tauri::command]
+#[specta::specta]
+pub fn show_build_in_dashboard(
+    app: tauri::AppHandle,
+    server: String,
+    build_id: i64,
+) -> Result<(), String> {
+    let url = {
+        let state = app.state::<Mutex<AppState>>();
+        let state_guard = state
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for getting settings: {e}"))?;
+        state_guard.settings.get_build_url(&server, build_id)?
+    };
+
+    let window = app
+        .get_webview_window(DASHBOARD_WINDOW_NAME)
+        .ok_or("Dashboard window not found")?;
+    window
+        .navigate(url)
+        .map_err(|e| format!("Failed to open build {build_id}: {e}"))?;
+
+    show_dashboard_window(app)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn close_dashboard_window(app: tauri::AppHandle) -> Result<(), String> {
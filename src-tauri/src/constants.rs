@@ -4,17 +4,40 @@ pub const APP_ID: &str = "com.pmease.quickbuild.traymonitor";
 pub const APP_NAME: &str = "traymonitor";
 
 pub const TRAY_ID: &str = "tray";
+/// Number of builds listed in the "Recent Builds" tray submenu
+pub const TRAY_RECENT_BUILDS: usize = 10;
 
 pub const MAIN_WINDOW_NAME: &str = "main";
 pub const DASHBOARD_WINDOW_NAME: &str = "dashboard";
 
+/// Name of the server of the single `server_url`, `user` and `token` settings, used when no
+/// server profile is defined
+pub const DEFAULT_SERVER_NAME: &str = "default";
+
 pub const TRAY_MONITOR_NOTIFICATION_TYPE: &str = "Tray Monitor & IDE Plugins";
 
+/// Upper bound of the delay between polls while the server keeps failing
+pub const MAX_POLL_BACKOFF_SECS: u64 = 300;
+
 pub const MAX_STORE_ROWS: usize = 100;
+/// Upper bound of the history age setting, about a hundred years
+pub const MAX_HISTORY_AGE_DAYS: u32 = 36_500;
+pub const HISTORY_DB_FILE_NAME: &str = "history.db";
+/// Legacy JSON stores, imported into the history database on first start
 pub const BUILD_STORE_FILE_NAME: &str = "builds.json";
 pub const ALERT_STORE_FILE_NAME: &str = "alerts.json";
+/// Encrypted access tokens and their key, used when no OS keyring is available
+pub const CREDENTIALS_FILE_NAME: &str = "credentials.json";
+pub const CREDENTIALS_KEY_FILE_NAME: &str = "credentials.key";
+
+/// Stands for a saved access token in the settings sent to the frontend, saving it back keeps
+/// the saved token
+pub const REDACTED_TOKEN: &str = "********";
+
+pub const DATE_TIME_FORMAT: &str = "[year]-[month]-[day] [hour]:[minute]:[second]";
 
-// pub const DATE_TIME_FORMAT: &str = "[year]-[month]-[day] [hour]:[minute]:[second]";
+/// Lines listed in the body of a digest notification
+pub const MAX_DIGEST_LINES: usize = 5;
 
 // pub const SETTINGS_WINDOW_WIDTH: i32 = 600;
 // pub const SETTINGS_WINDOW_HEIGHT: i32 = 400;
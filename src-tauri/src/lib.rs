@@ -3,11 +3,24 @@ use crate::{
     services::{
         alert_store::{AlertStore, create_alert_store},
         build_store::{BuildStore, create_build_store},
-        poll,
+        credentials::CredentialStore,
+        database::Database,
+        digest::Digest,
+        poll::{self, PollSignal},
+        quickbuild::QuickBuildError,
     },
-    types::{alert::Alert, build::Build, settings::AppSettings},
+    types::{
+        alert::Alert,
+        build::{Build, BuildQuery, BuildTransition},
+        server::ServerStatus,
+        settings::AppSettings,
+        watch::ConfigurationStatus,
+    },
+};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, RwLock},
 };
-use std::sync::{Arc, Mutex, RwLock};
 use tauri::{AppHandle, Manager, Wry};
 use tauri_plugin_autostart::MacosLauncher;
 use time::OffsetDateTime;
@@ -23,13 +36,43 @@ mod tray;
 mod types;
 mod utils;
 
+/// Polling state of a server
+#[derive(Debug, Clone, Default)]
+pub struct ServerState {
+    pub build_polling_error: Option<QuickBuildError>,
+    pub alert_polling_error: Option<QuickBuildError>,
+    pub last_polling_time: Option<OffsetDateTime>,
+    /// When the last completed poll started, polls requested before it are done
+    pub last_poll_start_time: Option<OffsetDateTime>,
+    pub next_polling_time: Option<OffsetDateTime>,
+    pub consecutive_failures: u32,
+    /// Newest build id fetched, including builds ignored by the notification rules
+    pub last_fetched_build_id: Option<i64>,
+    /// Newest alert time fetched, including alerts ignored by the notification rules
+    pub last_fetched_alert_time: Option<i64>,
+}
+
+impl ServerState {
+    pub fn get_polling_error(&self) -> Option<&QuickBuildError> {
+        self.build_polling_error
+            .as_ref()
+            .or(self.alert_polling_error.as_ref())
+    }
+}
+
 pub struct AppState {
     pub settings: AppSettings,
     pub build_store: Arc<RwLock<BuildStore>>,
     pub alert_store: Arc<RwLock<AlertStore>>,
-    pub build_polling_error: Option<String>,
-    pub alert_polling_error: Option<String>,
-    pub last_polling_time: Option<OffsetDateTime>,
+    /// Polling state of each server, by server name
+    pub servers: BTreeMap<String, ServerState>,
+    /// Titles of the notifications held back during quiet hours, only kept in memory so the
+    /// ones held when the app quits are lost
+    pub quiet_notifications: Vec<String>,
+    /// Builds and alerts waiting for the next digest notification
+    pub digest: Digest,
+    /// Latest build of each watched configuration, by server name and configuration id
+    pub configuration_statuses: BTreeMap<(String, i64), ConfigurationStatus>,
 }
 
 impl AppState {
@@ -38,109 +81,282 @@ impl AppState {
             settings,
             build_store: Arc::new(RwLock::new(builds_cache)),
             alert_store: Arc::new(RwLock::new(alert_store)),
-            build_polling_error: None,
-            alert_polling_error: None,
-            last_polling_time: None,
+            servers: BTreeMap::new(),
+            quiet_notifications: Vec::new(),
+            digest: Digest::default(),
+            configuration_statuses: BTreeMap::new(),
         }
     }
 
     pub fn init(app: &AppHandle<Wry>) -> Result<Self, String> {
         let settings = AppSettings::get(app)?;
-        let build_store = create_build_store()?;
-        let alert_store = create_alert_store()?;
+        let database = Database::open(&path::history_db_path()?)?;
+        let build_store = create_build_store(database.clone(), settings.get_retention())?;
+        let alert_store = create_alert_store(database, settings.get_retention())?;
         Ok(Self::new(settings, build_store, alert_store))
     }
 
     pub fn reload_settings(&mut self, app: &AppHandle<Wry>) -> Result<(), String> {
         let settings = AppSettings::get(app)?;
+        let retention = settings.get_retention();
+        let servers: Vec<String> = settings
+            .get_servers()
+            .into_iter()
+            .map(|server| server.name)
+            .collect();
+        self.servers.retain(|name, _| servers.contains(name));
+        self.configuration_statuses
+            .retain(|(server, _), _| servers.contains(server));
         self.settings = settings;
-        Ok(())
+
+        self.build_store
+            .write()
+            .map_err(|e| format!("Failed to acquire write lock for build store: {e}"))?
+            .set_retention(retention)?;
+        self.alert_store
+            .write()
+            .map_err(|e| format!("Failed to acquire write lock for alert store: {e}"))?
+            .set_retention(retention)
+    }
+
+    pub fn server_state_mut(&mut self, server: &str) -> &mut ServerState {
+        self.servers.entry(server.to_string()).or_default()
+    }
+
+    /// First polling error of the servers, if any
+    pub fn get_polling_error(&self) -> Option<&QuickBuildError> {
+        self.servers
+            .values()
+            .find_map(ServerState::get_polling_error)
+    }
+
+    /// Polling status of the configured servers, along with the build or alert polling error
+    /// picked by `polling_error`
+    pub fn get_server_statuses(
+        &self,
+        polling_error: impl Fn(&ServerState) -> Option<&QuickBuildError>,
+    ) -> Vec<ServerStatus> {
+        self.settings
+            .get_servers()
+            .into_iter()
+            .map(|server| {
+                let state = self.servers.get(&server.name).cloned().unwrap_or_default();
+                let error = polling_error(&state);
+                ServerStatus {
+                    name: server.name,
+                    error: error.map(|e| e.to_string()),
+                    error_kind: error.map(QuickBuildError::kind),
+                    last_polling_time: state.last_polling_time,
+                    consecutive_failures: state.consecutive_failures,
+                    next_retry_time: state
+                        .next_polling_time
+                        .filter(|_| state.consecutive_failures > 0),
+                }
+            })
+            .collect()
+    }
+
+    /// Latest polling time of the servers
+    pub fn get_last_polling_time(&self) -> Option<OffsetDateTime> {
+        self.servers
+            .values()
+            .filter_map(|server| server.last_polling_time)
+            .max()
     }
 
-    pub fn add_builds(&mut self, builds: Vec<Build>) -> Result<(), String> {
+    /// Saves the fetched builds, returns the ones that are new or changed
+    pub fn add_builds(&mut self, builds: Vec<Build>) -> Result<Vec<Build>, String> {
         if builds.is_empty() {
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         let mut builds_store = self
             .build_store
             .write()
             .map_err(|e| format!("Failed to acquire write lock for build store: {e}"))?;
-        builds_store.add_builds(builds);
         builds_store
-            .save()
+            .add_builds(builds)
             .map_err(|e| format!("Failed to save builds: {e}"))
     }
 
+    /// Updates the last known status of the configurations, returns the builds breaking or
+    /// fixing them
+    pub fn track_build_transitions(
+        &mut self,
+        builds: &[Build],
+    ) -> Result<Vec<BuildTransition>, String> {
+        if builds.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut builds_store = self
+            .build_store
+            .write()
+            .map_err(|e| format!("Failed to acquire write lock for build store: {e}"))?;
+        builds_store
+            .track_transitions(builds)
+            .map_err(|e| format!("Failed to track build transitions: {e}"))
+    }
+
     pub fn clear_builds(&mut self) -> Result<(), String> {
         let mut builds_store = self
             .build_store
             .write()
             .map_err(|e| format!("Failed to acquire write lock for build store: {e}"))?;
-        builds_store.clear();
         builds_store
-            .save()
-            .map_err(|e| format!("Failed to save builds: {e}"))
+            .clear()
+            .map_err(|e| format!("Failed to clear builds: {e}"))
     }
 
     pub fn get_builds(&self) -> Vec<Build> {
         self.build_store
             .read()
-            .map(|store| store.get_all())
+            .map_err(|e| format!("Failed to acquire read lock for build store: {e}"))
+            .and_then(|store| store.get_all())
             .unwrap_or_else(|e| {
-                tracing::error!("Failed to acquire read lock for build store: {e}");
+                tracing::error!("Failed to get builds: {e}");
                 Vec::new()
             })
     }
 
-    pub fn get_last_notified_build_id(&self) -> Option<i64> {
+    pub fn query_builds(&self, query: &BuildQuery) -> Result<(Vec<Build>, u32), String> {
+        self.build_store
+            .read()
+            .map_err(|e| format!("Failed to acquire read lock for build store: {e}"))?
+            .query(query)
+    }
+
+    pub fn set_build_read(
+        &mut self,
+        server: &str,
+        build_id: i64,
+        read: bool,
+    ) -> Result<bool, String> {
+        self.build_store
+            .write()
+            .map_err(|e| format!("Failed to acquire write lock for build store: {e}"))?
+            .set_read(server, build_id, read)
+    }
+
+    pub fn mark_all_builds_read(&mut self) -> Result<usize, String> {
+        self.build_store
+            .write()
+            .map_err(|e| format!("Failed to acquire write lock for build store: {e}"))?
+            .mark_all_read()
+    }
+
+    pub fn get_unread_builds_count(&self) -> u32 {
+        self.build_store
+            .read()
+            .map_err(|e| format!("Failed to acquire read lock for build store: {e}"))
+            .and_then(|store| store.get_unread_count())
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to count unread builds: {e}");
+                0
+            })
+    }
+
+    pub fn get_last_notified_build_id(&self, server: &str) -> Option<i64> {
         self.build_store
             .read()
             .ok()
-            .and_then(|store| store.get_last_notified_build_id())
+            .and_then(|store| store.get_last_notified_build_id(server))
+            .max(
+                self.servers
+                    .get(server)
+                    .and_then(|state| state.last_fetched_build_id),
+            )
     }
 
     pub fn get_alerts(&self) -> Vec<Alert> {
         self.alert_store
             .read()
-            .map(|store| store.get_all())
+            .map_err(|e| format!("Failed to acquire read lock for alert store: {e}"))
+            .and_then(|store| store.get_all())
             .unwrap_or_else(|e| {
-                tracing::error!("Failed to acquire read lock for alert store: {e}");
+                tracing::error!("Failed to get alerts: {e}");
                 Vec::new()
             })
     }
 
-    pub fn get_last_notified_time(&self) -> Option<i64> {
+    pub fn get_last_notified_time(&self, server: &str) -> Option<i64> {
         self.alert_store
             .read()
             .ok()
-            .and_then(|store| store.get_last_notified_time())
+            .and_then(|store| store.get_last_notified_time(server))
+            .max(
+                self.servers
+                    .get(server)
+                    .and_then(|state| state.last_fetched_alert_time),
+            )
     }
 
-    pub fn add_alerts(&mut self, alerts: Vec<Alert>) -> Result<(), String> {
+    /// Saves the fetched alerts, returns the ones that are new or changed
+    pub fn add_alerts(&mut self, alerts: Vec<Alert>) -> Result<Vec<Alert>, String> {
         if alerts.is_empty() {
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         let mut alerts_store = self
             .alert_store
             .write()
             .map_err(|e| format!("Failed to acquire write lock for alert store: {e}"))?;
-        alerts_store.add_alerts(alerts);
         alerts_store
-            .save()
+            .add_alerts(alerts)
             .map_err(|e| format!("Failed to save alerts: {e}"))
     }
 
+    pub fn acknowledge_alert(
+        &mut self,
+        server: &str,
+        alert_id: i64,
+        ack_time: i64,
+    ) -> Result<bool, String> {
+        let mut alerts_store = self
+            .alert_store
+            .write()
+            .map_err(|e| format!("Failed to acquire write lock for alert store: {e}"))?;
+        alerts_store.acknowledge(server, alert_id, ack_time)
+    }
+
+    pub fn set_alert_read(
+        &mut self,
+        server: &str,
+        alert_id: i64,
+        read: bool,
+    ) -> Result<bool, String> {
+        self.alert_store
+            .write()
+            .map_err(|e| format!("Failed to acquire write lock for alert store: {e}"))?
+            .set_read(server, alert_id, read)
+    }
+
+    pub fn mark_all_alerts_read(&mut self) -> Result<usize, String> {
+        self.alert_store
+            .write()
+            .map_err(|e| format!("Failed to acquire write lock for alert store: {e}"))?
+            .mark_all_read()
+    }
+
+    pub fn get_unread_alerts_count(&self) -> u32 {
+        self.alert_store
+            .read()
+            .map_err(|e| format!("Failed to acquire read lock for alert store: {e}"))
+            .and_then(|store| store.get_unread_count())
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to count unread alerts: {e}");
+                0
+            })
+    }
+
     pub fn clear_alerts(&mut self) -> Result<(), String> {
         let mut alerts_store = self
             .alert_store
             .write()
             .map_err(|e| format!("Failed to acquire write lock for alert store: {e}"))?;
-        alerts_store.clear();
         alerts_store
-            .save()
-            .map_err(|e| format!("Failed to save alerts: {e}"))
+            .clear()
+            .map_err(|e| format!("Failed to clear alerts: {e}"))
     }
 }
 
@@ -226,8 +442,14 @@ pub async fn run() {
 
             specta_builder.mount_events(&app_handle);
 
+            app_handle.manage(CredentialStore::init()?);
+            if let Err(e) = AppSettings::migrate_tokens(&app_handle) {
+                tracing::error!("Failed to move access tokens out of the settings store: {e}");
+            }
+
             let state = AppState::init(&app_handle)?;
             app_handle.manage(Mutex::new(state));
+            app_handle.manage(PollSignal::new());
 
             let main_win = app_handle
                 .get_webview_window(MAIN_WINDOW_NAME)
@@ -1,7 +1,10 @@
 use std::io;
 use std::path::PathBuf;
 
-use crate::constants::{ALERT_STORE_FILE_NAME, APP_ID, BUILD_STORE_FILE_NAME};
+use crate::constants::{
+    ALERT_STORE_FILE_NAME, APP_ID, BUILD_STORE_FILE_NAME, CREDENTIALS_FILE_NAME,
+    CREDENTIALS_KEY_FILE_NAME, HISTORY_DB_FILE_NAME,
+};
 
 pub fn config_dir() -> io::Result<PathBuf> {
     let path = platform_config_dir();
@@ -19,6 +22,12 @@ pub fn logs_dir() -> io::Result<PathBuf> {
     Ok(path)
 }
 
+pub fn history_db_path() -> Result<PathBuf, String> {
+    config_dir()
+        .map(|dir| dir.join(HISTORY_DB_FILE_NAME))
+        .map_err(|e| format!("Failed to get config directory: {e}"))
+}
+
 pub fn builds_store_path() -> Result<PathBuf, String> {
     config_dir()
         .map(|dir| dir.join(BUILD_STORE_FILE_NAME))
@@ -31,6 +40,18 @@ pub fn alerts_store_path() -> Result<PathBuf, String> {
         .map_err(|e| format!("Failed to get config directory: {e}"))
 }
 
+pub fn credentials_path() -> Result<PathBuf, String> {
+    config_dir()
+        .map(|dir| dir.join(CREDENTIALS_FILE_NAME))
+        .map_err(|e| format!("Failed to get config directory: {e}"))
+}
+
+pub fn credentials_key_path() -> Result<PathBuf, String> {
+    config_dir()
+        .map(|dir| dir.join(CREDENTIALS_KEY_FILE_NAME))
+        .map_err(|e| format!("Failed to get config directory: {e}"))
+}
+
 // pub fn log_file_path() -> PathBuf {
 //     platform_logs_dir().join(format!("{}.log", APP_NAME))
 // }
@@ -63,3 +63,38 @@ pub mod option_four_year_iso8601 {
         }
     }
 }
+
+/// Ids sent to the frontend as strings, as declared by the bindings, and read back from strings
+/// or numbers
+pub mod option_string_id {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(i64),
+        String(String),
+    }
+
+    pub fn serialize<S>(value: &Option<i64>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(id) => serializer.collect_str(id),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<Id>::deserialize(deserializer)? {
+            Some(Id::Number(id)) => Ok(Some(id)),
+            Some(Id::String(id)) => id.parse().map(Some).map_err(D::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
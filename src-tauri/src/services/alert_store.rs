@@ -1,52 +1,204 @@
-use crate::{constants::MAX_STORE_ROWS, path, types::alert::Alert};
-use ringbuffer::{AllocRingBuffer, RingBuffer};
+use std::str::FromStr;
 
+use rusqlite::{Connection, OptionalExtension, Row, params, types::Type};
+use time::OffsetDateTime;
+
+use crate::{
+    constants::DEFAULT_SERVER_NAME,
+    path,
+    services::database::{Database, Retention},
+    types::alert::{Alert, AlertCategory, AlertPriority},
+};
+
+const ALERT_COLUMNS: &str = "server, id, subject, priority, category, alert_message, alert_trigger, \
+    fixed, ctime, ack_time, read";
+
+/// Columns updated when an alert is saved again, every column but the server and the id. An
+/// alert fixed since is unread again.
+const ALERT_UPDATES: &str = "subject = excluded.subject, priority = excluded.priority, \
+    category = excluded.category, alert_message = excluded.alert_message, \
+    alert_trigger = excluded.alert_trigger, fixed = excluded.fixed, ctime = excluded.ctime, \
+    ack_time = excluded.ack_time, \
+    read = CASE WHEN alerts.fixed = excluded.fixed THEN alerts.read ELSE 0 END";
+
+/// Alert store structure, backed by the `alerts` table of the history database
 #[derive(Debug, Clone)]
 pub struct AlertStore {
-    alerts: AllocRingBuffer<Alert>,
+    db: Database,
+    retention: Retention,
 }
 
-impl Default for AlertStore {
-    fn default() -> Self {
-        Self::new()
+impl AlertStore {
+    pub fn new(db: Database, retention: Retention) -> Self {
+        Self { db, retention }
     }
-}
 
-impl AlertStore {
-    pub fn new() -> Self {
-        Self {
-            alerts: AllocRingBuffer::new(MAX_STORE_ROWS),
+    /// Changes how much history is kept, and removes the alerts falling outside of it
+    pub fn set_retention(&mut self, retention: Retention) -> Result<(), String> {
+        if self.retention == retention {
+            return Ok(());
         }
+
+        tracing::info!("Changing alert store retention to {retention:?}");
+        self.retention = retention;
+        apply_retention(&*self.db.connection()?, &self.retention)
     }
 
-    pub fn add_alerts(&mut self, mut new_alerts: Vec<Alert>) {
+    /// Inserts new alerts and updates the alerts already stored with the same server and id,
+    /// returns the alerts that are new or were fixed or acknowledged since
+    pub fn add_alerts(&mut self, mut new_alerts: Vec<Alert>) -> Result<Vec<Alert>, String> {
         tracing::debug!("Adding {} alerts to store", new_alerts.len());
-        new_alerts.sort_by_key(|alert| alert.id);
-        self.alerts.extend(new_alerts);
+
+        // keep the latest record when the same alert is returned more than once
+        new_alerts.reverse();
+        new_alerts.sort_by(|a, b| a.server.cmp(&b.server).then(a.id.cmp(&b.id)));
+        new_alerts.dedup_by(|a, b| a.server == b.server && a.id == b.id);
+
+        let mut changed_alerts = Vec::new();
+        let mut conn = self.db.connection()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {e}"))?;
+        {
+            let mut select = tx
+                .prepare_cached("SELECT fixed, ack_time FROM alerts WHERE server = ?1 AND id = ?2")
+                .map_err(|e| format!("Failed to prepare alert state query: {e}"))?;
+            let mut upsert = tx
+                .prepare_cached(&format!(
+                    "INSERT INTO alerts ({ALERT_COLUMNS}) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 0) \
+                     ON CONFLICT(server, id) DO UPDATE SET {ALERT_UPDATES}"
+                ))
+                .map_err(|e| format!("Failed to prepare alert upsert: {e}"))?;
+            for alert in new_alerts {
+                let old_state: Option<(bool, i64)> = select
+                    .query_row(params![alert.server, alert.id], |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })
+                    .optional()
+                    .map_err(|e| format!("Failed to query state of alert {}: {e}", alert.id))?;
+
+                upsert
+                    .execute(params![
+                        alert.server,
+                        alert.id,
+                        alert.subject,
+                        alert.priority.to_string(),
+                        alert.category.to_string(),
+                        alert.alert_message,
+                        alert.trigger,
+                        alert.fixed,
+                        alert.ctime,
+                        alert.ack_time,
+                    ])
+                    .map_err(|e| format!("Failed to save alert {}: {e}", alert.id))?;
+
+                if old_state.is_none_or(|state| state != (alert.fixed, alert.ack_time)) {
+                    changed_alerts.push(alert);
+                }
+            }
+        }
+
+        apply_retention(&tx, &self.retention)?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit alerts: {e}"))?;
+
+        tracing::debug!("{} alerts are new or changed", changed_alerts.len());
+        Ok(changed_alerts)
     }
 
-    pub fn clear(&mut self) {
-        tracing::debug!("Clearing alerts cache");
-        self.alerts.clear();
+    /// Records that the alert was acknowledged at the given time, in unix millis, returns
+    /// whether the alert is in the store
+    pub fn acknowledge(
+        &mut self,
+        server: &str,
+        alert_id: i64,
+        ack_time: i64,
+    ) -> Result<bool, String> {
+        let updated = self
+            .db
+            .connection()?
+            .execute(
+                "UPDATE alerts SET ack_time = ?3 WHERE server = ?1 AND id = ?2",
+                params![server, alert_id, ack_time],
+            )
+            .map_err(|e| format!("Failed to acknowledge alert {alert_id}: {e}"))?;
+        Ok(updated > 0)
+    }
+
+    /// Marks the alert as read or unread, returns whether the alert is in the store
+    pub fn set_read(&mut self, server: &str, alert_id: i64, read: bool) -> Result<bool, String> {
+        let updated = self
+            .db
+            .connection()?
+            .execute(
+                "UPDATE alerts SET read = ?3 WHERE server = ?1 AND id = ?2",
+                params![server, alert_id, read],
+            )
+            .map_err(|e| format!("Failed to mark alert {alert_id} as read: {e}"))?;
+        Ok(updated > 0)
+    }
+
+    /// Marks every alert as read, returns how many alerts were unread
+    pub fn mark_all_read(&mut self) -> Result<usize, String> {
+        self.db
+            .connection()?
+            .execute("UPDATE alerts SET read = 1 WHERE read = 0", [])
+            .map_err(|e| format!("Failed to mark alerts as read: {e}"))
+    }
+
+    pub fn get_unread_count(&self) -> Result<u32, String> {
+        self.db
+            .connection()?
+            .query_row("SELECT COUNT(*) FROM alerts WHERE read = 0", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| format!("Failed to count unread alerts: {e}"))
+    }
+
+    pub fn clear(&mut self) -> Result<(), String> {
+        tracing::debug!("Clearing alerts store");
+        self.db
+            .connection()?
+            .execute("DELETE FROM alerts", [])
+            .map_err(|e| format!("Failed to clear alerts: {e}"))?;
+        Ok(())
     }
 
-    pub fn get_all(&self) -> Vec<Alert> {
-        let mut all: Vec<Alert> = self.alerts.clone().into_iter().collect();
-        all.sort_by_key(|alert| alert.ctime);
-        all.reverse();
-        tracing::debug!("Getting {} alerts from store", all.len());
-        all
+    pub fn get_all(&self) -> Result<Vec<Alert>, String> {
+        let conn = self.db.connection()?;
+        let mut stmt = conn
+            .prepare_cached(&format!(
+                "SELECT {ALERT_COLUMNS} FROM alerts ORDER BY ctime DESC, id DESC"
+            ))
+            .map_err(|e| format!("Failed to prepare alerts query: {e}"))?;
+        let alerts = stmt
+            .query_map([], alert_from_row)
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to query alerts: {e}"))?;
+        tracing::debug!("Getting {} alerts from store", alerts.len());
+        Ok(alerts)
     }
 
-    pub fn get_last_notified_time(&self) -> Option<i64> {
-        self.alerts.back().map(|alert| alert.ctime)
+    pub fn get_last_notified_time(&self, server: &str) -> Option<i64> {
+        let conn = self.db.connection().ok()?;
+        conn.query_row(
+            "SELECT MAX(ctime) FROM alerts WHERE server = ?1",
+            [server],
+            |row| row.get(0),
+        )
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to query last notified time: {e}");
+            None
+        })
     }
 
-    pub fn load(&mut self) -> Result<(), String> {
+    /// Imports the alerts of the JSON store used by previous versions, then renames the file
+    /// so that the import only happens once
+    pub fn import_legacy_store(&mut self) -> Result<(), String> {
         let store_path = path::alerts_store_path()?;
 
         if !store_path.exists() {
-            tracing::info!("Alerts store file does not exist, creating new store");
             return Ok(());
         }
 
@@ -54,63 +206,161 @@ impl AlertStore {
             Ok(content) => match serde_json::from_str::<Vec<Alert>>(&content) {
                 Ok(alerts) => alerts,
                 Err(e) => {
-                    tracing::warn!("Failed to deserialize alerts: {e}");
+                    tracing::warn!("Failed to deserialize legacy alerts store: {e}");
                     Vec::new()
                 }
             },
             Err(e) => {
-                tracing::warn!("Failed to read alerts store: {e}");
+                tracing::warn!("Failed to read legacy alerts store: {e}");
                 Vec::new()
             }
         };
 
-        self.alerts.clear();
-        self.add_alerts(alerts);
+        tracing::info!("Importing {} alerts from legacy alerts store", alerts.len());
+        let alerts = alerts
+            .into_iter()
+            .map(|alert| Alert {
+                server: DEFAULT_SERVER_NAME.to_string(),
+                ..alert
+            })
+            .collect();
+        self.add_alerts(alerts)?;
 
-        Ok(())
+        let migrated_path = store_path.with_extension("json.migrated");
+        std::fs::rename(&store_path, &migrated_path)
+            .map_err(|e| format!("Failed to rename legacy alerts store: {e}"))
     }
+}
 
-    pub fn save(&self) -> Result<(), String> {
-        let store_path = path::alerts_store_path()?;
-        // Ensure parent directory exists
-        if let Some(parent) = store_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create builds store directory: {e}"))?;
-        }
+fn apply_retention(conn: &Connection, retention: &Retention) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM alerts WHERE rowid IN (SELECT rowid FROM \
+         (SELECT rowid, ROW_NUMBER() OVER \
+         (PARTITION BY server ORDER BY ctime DESC, id DESC) AS position FROM alerts) \
+         WHERE position > ?1)",
+        [retention.max_rows as i64],
+    )
+    .map_err(|e| format!("Failed to trim alerts: {e}"))?;
+
+    if let Some(cutoff) = retention.cutoff_millis(OffsetDateTime::now_utc()) {
+        conn.execute("DELETE FROM alerts WHERE ctime < ?1", [cutoff])
+            .map_err(|e| format!("Failed to remove expired alerts: {e}"))?;
+    }
+
+    Ok(())
+}
 
-        let alerts = self.get_all();
-        tracing::debug!("Saving {} alerts to alerts store file", alerts.len());
+fn alert_from_row(row: &Row) -> rusqlite::Result<Alert> {
+    let priority: String = row.get(3)?;
+    let priority = AlertPriority::from_str(&priority)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(3, Type::Text, Box::new(e)))?;
+    let category: String = row.get(4)?;
+    let category = AlertCategory::from_str(&category)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(4, Type::Text, Box::new(e)))?;
 
-        let content = serde_json::to_string_pretty(&alerts)
-            .map_err(|e| format!("Failed to serialize alerts: {e}"))?;
+    Ok(Alert {
+        server: row.get(0)?,
+        id: row.get(1)?,
+        subject: row.get(2)?,
+        priority,
+        category,
+        alert_message: row.get(5)?,
+        trigger: row.get(6)?,
+        fixed: row.get(7)?,
+        ctime: row.get(8)?,
+        ack_time: row.get(9)?,
+        read: row.get(10)?,
+    })
+}
 
-        // Write to a temporary file first, then rename (atomic operation)
-        let temp_path = store_path.with_extension("tmp");
+pub fn create_alert_store(db: Database, retention: Retention) -> Result<AlertStore, String> {
+    let mut store = AlertStore::new(db, retention);
+    store.import_legacy_store()?;
+    Ok(store)
+}
 
-        std::fs::write(&temp_path, content).map_err(|e| {
-            tracing::error!("Failed to write alerts store file: {e}");
-            format!("Failed to write alerts store file: {e}")
-        })?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if let Err(rename_err) = std::fs::rename(&temp_path, &store_path) {
-            tracing::error!("Failed to finalize alerts store file: {rename_err}");
-            if let Err(remove_err) = std::fs::remove_file(&temp_path) {
-                tracing::warn!(
-                    "Failed to remove alerts temp file after rename failure: {remove_err}"
-                );
-            }
-            return Err(format!(
-                "Failed to finalize alerts store file: {rename_err}"
-            ));
-        }
+    #[test]
+    fn test_add_and_get_alerts() {
+        let mut store = AlertStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        store
+            .add_alerts(vec![
+                Alert::test().id(1).ctime(2000).build(),
+                Alert::test().id(2).ctime(1000).build(),
+            ])
+            .unwrap();
 
-        tracing::debug!("Saved {} alerts to alerts store file", self.alerts.len());
-        Ok(())
+        let alerts = store.get_all().unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].id, 1);
+        assert!(matches!(alerts[0].priority, AlertPriority::High));
+        assert_eq!(store.get_last_notified_time("default"), Some(2000));
+
+        store.clear().unwrap();
+        assert!(store.get_all().unwrap().is_empty());
+        assert_eq!(store.get_last_notified_time("default"), None);
     }
-}
 
-pub fn create_alert_store() -> Result<AlertStore, String> {
-    let mut store = AlertStore::new();
-    store.load()?;
-    Ok(store)
+    #[test]
+    fn test_add_alerts_upserts_by_id() {
+        let mut store = AlertStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        let changed = store
+            .add_alerts(vec![
+                Alert::test().id(1).ctime(1000).build(),
+                Alert::test().id(1).ctime(1000).build(),
+            ])
+            .unwrap();
+        assert_eq!(changed.len(), 1);
+
+        assert!(
+            store
+                .add_alerts(vec![Alert::test().id(1).ctime(1000).build()])
+                .unwrap()
+                .is_empty()
+        );
+
+        let fixed = Alert::test().id(1).ctime(1000).fixed(true).build();
+        assert_eq!(store.add_alerts(vec![fixed]).unwrap().len(), 1);
+        assert_eq!(store.get_all().unwrap().len(), 1);
+        assert!(store.get_all().unwrap()[0].fixed);
+    }
+
+    #[test]
+    fn test_acknowledge_alert() {
+        let mut store = AlertStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        store
+            .add_alerts(vec![Alert::test().id(1).ctime(1000).build()])
+            .unwrap();
+
+        assert!(store.acknowledge("default", 1, 2000).unwrap());
+        assert!(!store.acknowledge("default", 2, 2000).unwrap());
+        assert!(store.get_all().unwrap()[0].is_acknowledged());
+    }
+
+    #[test]
+    fn test_read_flags() {
+        let mut store = AlertStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        store
+            .add_alerts(vec![
+                Alert::test().id(1).ctime(1000).build(),
+                Alert::test().id(2).ctime(2000).build(),
+            ])
+            .unwrap();
+        assert_eq!(store.get_unread_count().unwrap(), 2);
+
+        assert!(store.set_read("default", 2, true).unwrap());
+        assert_eq!(store.get_unread_count().unwrap(), 1);
+        assert_eq!(store.mark_all_read().unwrap(), 1);
+        assert_eq!(store.get_unread_count().unwrap(), 0);
+
+        let fixed = Alert::test().id(1).ctime(1000).fixed(true).build();
+        store
+            .add_alerts(vec![fixed, Alert::test().id(2).ctime(2000).build()])
+            .unwrap();
+        assert_eq!(store.get_unread_count().unwrap(), 1);
+        assert!(!store.get_all().unwrap()[1].read);
+    }
 }
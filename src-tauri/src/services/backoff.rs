@@ -0,0 +1,124 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Upper bound of the exponent, so the multiplier never exceeds 2^10 of the poll interval
+const MAX_EXPONENT: u32 = 10;
+
+/// Exponential backoff with jitter used by the poll loop while the server keeps failing.
+///
+/// The delay doubles with every consecutive failure, is capped at `max_delay` and resets to
+/// the regular poll interval on the first success.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    consecutive_failures: u32,
+    max_delay: Duration,
+}
+
+impl Backoff {
+    pub fn new(max_delay: Duration) -> Self {
+        Self {
+            consecutive_failures: 0,
+            max_delay,
+        }
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+    }
+
+    pub fn record_failure(&mut self) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+    }
+
+    /// Returns how long to wait before the next poll
+    pub fn next_delay(&self, interval: Duration) -> Duration {
+        self.delay_with_jitter(interval, random_jitter())
+    }
+
+    /// `jitter` is in `[0, 1)` and spreads the delay over `[delay / 2, delay]`, so clients
+    /// failing at the same time do not retry at the same time
+    fn delay_with_jitter(&self, interval: Duration, jitter: f64) -> Duration {
+        if self.consecutive_failures == 0 {
+            return interval;
+        }
+
+        let exponent = self.consecutive_failures.min(MAX_EXPONENT);
+        let delay = interval
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay.max(interval));
+
+        delay.mul_f64(0.5 + jitter / 2.0).max(interval)
+    }
+}
+
+fn random_jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    nanos as f64 / 1_000_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn test_no_backoff_without_failures() {
+        let backoff = Backoff::new(Duration::from_secs(300));
+        assert_eq!(backoff.delay_with_jitter(INTERVAL, 0.0), INTERVAL);
+        assert_eq!(backoff.delay_with_jitter(INTERVAL, 0.99), INTERVAL);
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let mut backoff = Backoff::new(Duration::from_secs(300));
+
+        backoff.record_failure();
+        assert_eq!(
+            backoff.delay_with_jitter(INTERVAL, 1.0),
+            Duration::from_secs(20)
+        );
+
+        backoff.record_failure();
+        assert_eq!(
+            backoff.delay_with_jitter(INTERVAL, 1.0),
+            Duration::from_secs(40)
+        );
+
+        for _ in 0..20 {
+            backoff.record_failure();
+        }
+        assert_eq!(backoff.consecutive_failures(), 22);
+        assert_eq!(
+            backoff.delay_with_jitter(INTERVAL, 1.0),
+            Duration::from_secs(300)
+        );
+        assert_eq!(
+            backoff.delay_with_jitter(INTERVAL, 0.0),
+            Duration::from_secs(150)
+        );
+    }
+
+    #[test]
+    fn test_backoff_never_shorter_than_interval() {
+        let mut backoff = Backoff::new(Duration::from_secs(5));
+        backoff.record_failure();
+        assert_eq!(backoff.delay_with_jitter(INTERVAL, 0.0), INTERVAL);
+    }
+
+    #[test]
+    fn test_backoff_resets_on_success() {
+        let mut backoff = Backoff::new(Duration::from_secs(300));
+        backoff.record_failure();
+        backoff.record_failure();
+        backoff.record_success();
+        assert_eq!(backoff.consecutive_failures(), 0);
+        assert_eq!(backoff.delay_with_jitter(INTERVAL, 0.5), INTERVAL);
+    }
+}
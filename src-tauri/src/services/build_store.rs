@@ -1,54 +1,317 @@
-use crate::{constants::MAX_STORE_ROWS, path, types::build::Build};
-use ringbuffer::{AllocRingBuffer, RingBuffer};
+use std::str::FromStr;
 
-/// Build store structure
+use rusqlite::{
+    Connection, OptionalExtension, Row, params, params_from_iter,
+    types::{Type, Value},
+};
+use time::OffsetDateTime;
+
+use crate::{
+    constants::DEFAULT_SERVER_NAME,
+    path,
+    services::database::{Database, Retention, from_millis, to_millis},
+    types::build::{Build, BuildQuery, BuildStatus, BuildTransition, SortOrder, TransitionKind},
+};
+
+/// Columns updated when a build is saved again, every column but the server and the id. A build whose status
+/// changed is unread again.
+const BUILD_UPDATES: &str = "configuration = excluded.configuration, \
+    configuration_path = excluded.configuration_path, \
+    master_node_address = excluded.master_node_address, requester = excluded.requester, \
+    requester_name = excluded.requester_name, canceller = excluded.canceller, \
+    canceller_name = excluded.canceller_name, version = excluded.version, \
+    status = excluded.status, begin_date = excluded.begin_date, \
+    status_date = excluded.status_date, duration = excluded.duration, \
+    wait_duration = excluded.wait_duration, \
+    read = CASE WHEN builds.status = excluded.status THEN builds.read ELSE 0 END";
+
+/// Upper bound of the page size of a build query
+const MAX_QUERY_LIMIT: u32 = 500;
+
+const BUILD_COLUMNS: &str = "server, id, configuration, configuration_path, master_node_address, \
+    requester, requester_name, canceller, canceller_name, version, status, begin_date, \
+    status_date, duration, wait_duration, read";
+
+/// Last known health of a configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ConfigurationState {
+    last_build_id: i64,
+    /// Number of failed builds in a row, 0 while the configuration is healthy
+    failure_count: u32,
+    /// Status date of the build breaking the configuration, in millis
+    failing_since: Option<i64>,
+}
+
+/// Build store structure, backed by the `builds` table of the history database
 #[derive(Debug, Clone)]
 pub struct BuildStore {
-    builds: AllocRingBuffer<Build>,
+    db: Database,
+    retention: Retention,
 }
 
-impl Default for BuildStore {
-    fn default() -> Self {
-        Self::new()
+impl BuildStore {
+    pub fn new(db: Database, retention: Retention) -> Self {
+        Self { db, retention }
     }
-}
 
-impl BuildStore {
-    pub fn new() -> Self {
-        Self {
-            builds: AllocRingBuffer::new(MAX_STORE_ROWS),
+    /// Changes how much history is kept, and removes the builds falling outside of it
+    pub fn set_retention(&mut self, retention: Retention) -> Result<(), String> {
+        if self.retention == retention {
+            return Ok(());
         }
+
+        tracing::info!("Changing build store retention to {retention:?}");
+        self.retention = retention;
+        apply_retention(&*self.db.connection()?, &self.retention)
     }
 
-    pub fn add_builds(&mut self, mut new_builds: Vec<Build>) {
-        tracing::debug!("Adding {} builds to cache", new_builds.len());
+    /// Inserts new builds and updates the builds already stored with the same server and id,
+    /// returns the builds that are new or whose status changed
+    pub fn add_builds(&mut self, mut new_builds: Vec<Build>) -> Result<Vec<Build>, String> {
+        tracing::debug!("Adding {} builds to store", new_builds.len());
+
+        // keep the latest record when the same build is returned more than once
+        new_builds.reverse();
+        new_builds.sort_by(|a, b| a.server.cmp(&b.server).then(a.id.cmp(&b.id)));
+        new_builds.dedup_by(|a, b| a.server == b.server && a.id == b.id);
 
-        new_builds.sort_by_key(|build| build.id);
-        self.builds.extend(new_builds);
+        let mut changed_builds = Vec::new();
+        let mut conn = self.db.connection()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {e}"))?;
+        {
+            let mut select = tx
+                .prepare_cached("SELECT status FROM builds WHERE server = ?1 AND id = ?2")
+                .map_err(|e| format!("Failed to prepare build status query: {e}"))?;
+            let mut upsert = tx
+                .prepare_cached(&format!(
+                    "INSERT INTO builds ({BUILD_COLUMNS}) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, 0) \
+                     ON CONFLICT(server, id) DO UPDATE SET {BUILD_UPDATES}"
+                ))
+                .map_err(|e| format!("Failed to prepare build upsert: {e}"))?;
+            for build in new_builds {
+                let old_status: Option<String> = select
+                    .query_row(params![build.server, build.id], |row| row.get(0))
+                    .optional()
+                    .map_err(|e| format!("Failed to query status of build {}: {e}", build.id))?;
+
+                upsert
+                    .execute(params![
+                        build.server,
+                        build.id,
+                        build.configuration,
+                        build.configuration_path,
+                        build.master_node_address,
+                        build.requester,
+                        build.requester_name,
+                        build.canceller,
+                        build.canceller_name,
+                        build.version,
+                        build.status.to_string(),
+                        to_millis(build.begin_date),
+                        build.status_date.map(to_millis),
+                        build.duration,
+                        build.wait_duration,
+                    ])
+                    .map_err(|e| format!("Failed to save build {}: {e}", build.id))?;
+
+                if old_status.is_none_or(|status| status != build.status.to_string()) {
+                    changed_builds.push(build);
+                }
+            }
+        }
+
+        apply_retention(&tx, &self.retention)?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit builds: {e}"))?;
+
+        tracing::debug!("{} builds are new or changed", changed_builds.len());
+        Ok(changed_builds)
+    }
+
+    /// Updates the last known state of the configurations of the given builds, returns the
+    /// builds breaking or fixing their configuration
+    pub fn track_transitions(&mut self, builds: &[Build]) -> Result<Vec<BuildTransition>, String> {
+        let mut builds: Vec<&Build> = builds.iter().collect();
+        builds.sort_by_key(|build| build.id);
+
+        let mut transitions = Vec::new();
+        let mut conn = self.db.connection()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {e}"))?;
+        {
+            let mut select = tx
+                .prepare_cached(
+                    "SELECT last_build_id, failure_count, failing_since \
+                     FROM configuration_states WHERE server = ?1 AND configuration = ?2",
+                )
+                .map_err(|e| format!("Failed to prepare configuration state query: {e}"))?;
+            let mut upsert = tx
+                .prepare_cached(
+                    "INSERT INTO configuration_states \
+                     (server, configuration, last_build_id, failure_count, failing_since) \
+                     VALUES (?1, ?2, ?3, ?4, ?5) \
+                     ON CONFLICT(server, configuration) DO UPDATE SET \
+                     last_build_id = excluded.last_build_id, \
+                     failure_count = excluded.failure_count, \
+                     failing_since = excluded.failing_since",
+                )
+                .map_err(|e| format!("Failed to prepare configuration state upsert: {e}"))?;
+
+            for build in builds {
+                let old_state = select
+                    .query_row(params![build.server, build.configuration], |row| {
+                        Ok(ConfigurationState {
+                            last_build_id: row.get(0)?,
+                            failure_count: row.get(1)?,
+                            failing_since: row.get(2)?,
+                        })
+                    })
+                    .optional()
+                    .map_err(|e| {
+                        format!(
+                            "Failed to query state of configuration {}: {e}",
+                            build.configuration
+                        )
+                    })?;
+
+                let Some((state, transition)) = next_configuration_state(old_state, build) else {
+                    continue;
+                };
+
+                upsert
+                    .execute(params![
+                        build.server,
+                        build.configuration,
+                        state.last_build_id,
+                        state.failure_count,
+                        state.failing_since,
+                    ])
+                    .map_err(|e| {
+                        format!(
+                            "Failed to save state of configuration {}: {e}",
+                            build.configuration
+                        )
+                    })?;
+                transitions.extend(transition);
+            }
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit configuration states: {e}"))?;
+        Ok(transitions)
     }
 
-    pub fn clear(&mut self) {
-        tracing::debug!("Clearing builds cache");
-        self.builds.clear();
+    /// Marks the build as read or unread, returns whether the build is in the store
+    pub fn set_read(&mut self, server: &str, build_id: i64, read: bool) -> Result<bool, String> {
+        let updated = self
+            .db
+            .connection()?
+            .execute(
+                "UPDATE builds SET read = ?3 WHERE server = ?1 AND id = ?2",
+                params![server, build_id, read],
+            )
+            .map_err(|e| format!("Failed to mark build {build_id} as read: {e}"))?;
+        Ok(updated > 0)
     }
 
-    pub fn get_all(&self) -> Vec<Build> {
-        let mut all: Vec<Build> = self.builds.clone().into_iter().collect();
-        all.sort_by_key(|build| build.id);
-        all.reverse();
-        tracing::debug!("Getting {} builds from store", all.len());
-        all
+    /// Marks every build as read, returns how many builds were unread
+    pub fn mark_all_read(&mut self) -> Result<usize, String> {
+        self.db
+            .connection()?
+            .execute("UPDATE builds SET read = 1 WHERE read = 0", [])
+            .map_err(|e| format!("Failed to mark builds as read: {e}"))
     }
 
-    pub fn get_last_notified_build_id(&self) -> Option<i64> {
-        self.builds.back().map(|build| build.id)
+    pub fn get_unread_count(&self) -> Result<u32, String> {
+        self.db
+            .connection()?
+            .query_row("SELECT COUNT(*) FROM builds WHERE read = 0", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| format!("Failed to count unread builds: {e}"))
     }
 
-    pub fn load(&mut self) -> Result<(), String> {
+    pub fn clear(&mut self) -> Result<(), String> {
+        tracing::debug!("Clearing builds store");
+        self.db
+            .connection()?
+            .execute("DELETE FROM builds", [])
+            .map_err(|e| format!("Failed to clear builds: {e}"))?;
+        Ok(())
+    }
+
+    pub fn get_all(&self) -> Result<Vec<Build>, String> {
+        let conn = self.db.connection()?;
+        let mut stmt = conn
+            .prepare_cached(&format!(
+                "SELECT {BUILD_COLUMNS} FROM builds ORDER BY begin_date DESC, id DESC"
+            ))
+            .map_err(|e| format!("Failed to prepare builds query: {e}"))?;
+        let builds = stmt
+            .query_map([], build_from_row)
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to query builds: {e}"))?;
+        tracing::debug!("Getting {} builds from store", builds.len());
+        Ok(builds)
+    }
+
+    /// Returns the page of builds matching the query, along with the total number of matches
+    pub fn query(&self, query: &BuildQuery) -> Result<(Vec<Build>, u32), String> {
+        let (condition, mut values) = build_query_condition(query);
+        let conn = self.db.connection()?;
+
+        let total: u32 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM builds WHERE {condition}"),
+                params_from_iter(values.iter()),
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to count builds: {e}"))?;
+
+        let order = match query.sort_order {
+            SortOrder::NewestFirst => "DESC",
+            SortOrder::OldestFirst => "ASC",
+        };
+        values.push(Value::Integer(query.limit.min(MAX_QUERY_LIMIT) as i64));
+        values.push(Value::Integer(query.offset as i64));
+
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {BUILD_COLUMNS} FROM builds WHERE {condition} \
+                 ORDER BY begin_date {order}, id {order} LIMIT ? OFFSET ?"
+            ))
+            .map_err(|e| format!("Failed to prepare builds query: {e}"))?;
+        let builds = stmt
+            .query_map(params_from_iter(values.iter()), build_from_row)
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to query builds: {e}"))?;
+
+        Ok((builds, total))
+    }
+
+    pub fn get_last_notified_build_id(&self, server: &str) -> Option<i64> {
+        let conn = self.db.connection().ok()?;
+        conn.query_row(
+            "SELECT MAX(id) FROM builds WHERE server = ?1",
+            [server],
+            |row| row.get(0),
+        )
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to query last notified build id: {e}");
+            None
+        })
+    }
+
+    /// Imports the builds of the JSON store used by previous versions, then renames the file
+    /// so that the import only happens once
+    pub fn import_legacy_store(&mut self) -> Result<(), String> {
         let store_path = path::builds_store_path()?;
 
         if !store_path.exists() {
-            tracing::info!("Builds store file does not exist, creating new store");
             return Ok(());
         }
 
@@ -56,60 +319,547 @@ impl BuildStore {
             Ok(content) => match serde_json::from_str::<Vec<Build>>(&content) {
                 Ok(builds) => builds,
                 Err(e) => {
-                    tracing::warn!("Failed to deserialize builds: {e}, creating new store");
+                    tracing::warn!("Failed to deserialize legacy builds store: {e}");
                     Vec::new()
                 }
             },
             Err(e) => {
-                tracing::warn!("Failed to read builds store: {e}, creating new store");
+                tracing::warn!("Failed to read legacy builds store: {e}");
                 Vec::new()
             }
         };
 
-        self.builds.clear();
-        self.add_builds(builds);
+        tracing::info!("Importing {} builds from legacy builds store", builds.len());
+        let builds = builds
+            .into_iter()
+            .map(|build| Build {
+                server: DEFAULT_SERVER_NAME.to_string(),
+                ..build
+            })
+            .collect();
+        self.add_builds(builds)?;
 
-        Ok(())
+        let migrated_path = store_path.with_extension("json.migrated");
+        std::fs::rename(&store_path, &migrated_path)
+            .map_err(|e| format!("Failed to rename legacy builds store: {e}"))
+    }
+}
+
+/// Computes the state of the configuration after the given build, `None` when the build
+/// leaves the state unchanged, e.g. a running or cancelled build, or one already seen
+fn next_configuration_state(
+    state: Option<ConfigurationState>,
+    build: &Build,
+) -> Option<(ConfigurationState, Option<BuildTransition>)> {
+    if state.is_some_and(|state| state.last_build_id >= build.id) {
+        return None;
     }
 
-    pub fn save(&self) -> Result<(), String> {
-        let store_path = path::builds_store_path()?;
+    let status_date = to_millis(build.get_status_date());
+    let transition = |kind, failure_count, failing_duration| BuildTransition {
+        kind,
+        build: build.clone(),
+        failure_count,
+        failing_duration,
+    };
 
-        // Ensure parent directory exists
-        if let Some(parent) = store_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create builds store directory: {e}"))?;
-        }
+    if build.status.is_failure() {
+        let failure_count = state.map_or(0, |state| state.failure_count);
+        let next = ConfigurationState {
+            last_build_id: build.id,
+            failure_count: failure_count + 1,
+            failing_since: state
+                .and_then(|state| state.failing_since)
+                .or(Some(status_date)),
+        };
+        // nothing is known about a configuration seen for the first time
+        let broke = state.is_some() && failure_count == 0;
+        Some((
+            next,
+            broke.then(|| transition(TransitionKind::Broke, 1, None)),
+        ))
+    } else if build.status.is_success() {
+        let next = ConfigurationState {
+            last_build_id: build.id,
+            failure_count: 0,
+            failing_since: None,
+        };
+        let fixed = state.filter(|state| state.failure_count > 0).map(|state| {
+            let failing_duration = state.failing_since.map(|since| status_date - since);
+            transition(TransitionKind::Fixed, state.failure_count, failing_duration)
+        });
+        Some((next, fixed))
+    } else {
+        None
+    }
+}
 
-        let content = serde_json::to_string_pretty(&self.get_all())
-            .map_err(|e| format!("Failed to serialize builds: {e}"))?;
+fn apply_retention(conn: &Connection, retention: &Retention) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM builds WHERE rowid IN (SELECT rowid FROM \
+         (SELECT rowid, ROW_NUMBER() OVER \
+         (PARTITION BY server ORDER BY begin_date DESC, id DESC) AS position FROM builds) \
+         WHERE position > ?1)",
+        [retention.max_rows as i64],
+    )
+    .map_err(|e| format!("Failed to trim builds: {e}"))?;
 
-        // Write to a temporary file first, then rename (atomic operation)
-        let temp_path = store_path.with_extension("tmp");
+    if let Some(cutoff) = retention.cutoff_millis(OffsetDateTime::now_utc()) {
+        conn.execute("DELETE FROM builds WHERE begin_date < ?1", [cutoff])
+            .map_err(|e| format!("Failed to remove expired builds: {e}"))?;
+    }
 
-        std::fs::write(&temp_path, content).map_err(|e| {
-            tracing::error!("Failed to write builds store file: {e}");
-            format!("Failed to write builds store file: {e}")
-        })?;
+    Ok(())
+}
 
-        if let Err(rename_err) = std::fs::rename(&temp_path, &store_path) {
-            tracing::error!("Failed to finalize builds store file: {rename_err}");
-            // Clean up the temp file to avoid leaving orphaned files on disk
-            if let Err(remove_err) = std::fs::remove_file(&temp_path) {
-                tracing::warn!("Failed to remove temp file after rename failure: {remove_err}");
-            }
-            return Err(format!(
-                "Failed to finalize builds store file: {rename_err}"
-            ));
-        }
+/// Translates the filters of the query into an SQL condition and its parameters
+fn build_query_condition(query: &BuildQuery) -> (String, Vec<Value>) {
+    let mut conditions = vec!["1 = 1".to_string()];
+    let mut values = Vec::new();
 
-        tracing::debug!("Saved {} builds to builds store file", self.builds.len());
-        Ok(())
+    if let Some(server) = query.server.as_deref().filter(|s| !s.is_empty()) {
+        conditions.push("server = ?".to_string());
+        values.push(Value::Text(server.to_string()));
+    }
+
+    if !query.statuses.is_empty() {
+        let placeholders = vec!["?"; query.statuses.len()].join(", ");
+        conditions.push(format!("status IN ({placeholders})"));
+        values.extend(
+            query
+                .statuses
+                .iter()
+                .map(|status| Value::Text(status.to_string())),
+        );
+    }
+
+    if let Some(path) = query
+        .configuration_path
+        .as_deref()
+        .filter(|p| !p.is_empty())
+    {
+        conditions.push("configuration_path GLOB ?".to_string());
+        values.push(Value::Text(to_path_glob(path)));
+    }
+
+    if let Some(requester) = query.requester.as_deref().filter(|r| !r.is_empty()) {
+        conditions.push("requester_name LIKE ? ESCAPE '\\'".to_string());
+        values.push(Value::Text(to_contains_pattern(requester)));
+    }
+
+    if let Some(version) = query.version.as_deref().filter(|v| !v.is_empty()) {
+        conditions.push("version LIKE ? ESCAPE '\\'".to_string());
+        values.push(Value::Text(to_contains_pattern(version)));
+    }
+
+    if let Some(from) = query.begin_date_from {
+        conditions.push("begin_date >= ?".to_string());
+        values.push(Value::Integer(to_millis(from)));
+    }
+
+    if let Some(to) = query.begin_date_to {
+        conditions.push("begin_date < ?".to_string());
+        values.push(Value::Integer(to_millis(to)));
+    }
+
+    (conditions.join(" AND "), values)
+}
+
+/// A path containing glob wildcards is used as is, anything else matches as a prefix
+fn to_path_glob(path: &str) -> String {
+    if path.contains(['*', '?']) {
+        return path.to_string();
     }
+
+    let mut glob = String::with_capacity(path.len() + 1);
+    for c in path.chars() {
+        if c == '[' {
+            glob.push_str("[[]");
+        } else {
+            glob.push(c);
+        }
+    }
+    glob.push('*');
+    glob
+}
+
+fn to_contains_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+fn build_from_row(row: &Row) -> rusqlite::Result<Build> {
+    let status: String = row.get(10)?;
+    let status = BuildStatus::from_str(&status)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(10, Type::Text, Box::new(e)))?;
+    let begin_date = from_millis(row.get(11)?)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(11, Type::Integer, Box::new(e)))?;
+    let status_date = row
+        .get::<_, Option<i64>>(12)?
+        .map(from_millis)
+        .transpose()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(12, Type::Integer, Box::new(e)))?;
+
+    Ok(Build {
+        server: row.get(0)?,
+        id: row.get(1)?,
+        configuration: row.get(2)?,
+        configuration_path: row.get(3)?,
+        master_node_address: row.get(4)?,
+        requester: row.get(5)?,
+        requester_name: row.get(6)?,
+        canceller: row.get(7)?,
+        canceller_name: row.get(8)?,
+        version: row.get(9)?,
+        status,
+        begin_date,
+        status_date,
+        duration: row.get(13)?,
+        wait_duration: row.get(14)?,
+        read: row.get(15)?,
+    })
 }
 
-pub fn create_build_store() -> Result<BuildStore, String> {
-    let mut store = BuildStore::new();
-    store.load()?;
+pub fn create_build_store(db: Database, retention: Retention) -> Result<BuildStore, String> {
+    let mut store = BuildStore::new(db, retention);
+    store.import_legacy_store()?;
     Ok(store)
 }
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_add_and_get_builds() {
+        let mut store = BuildStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        store
+            .add_builds(vec![
+                Build::test().id(2).status(BuildStatus::Failed).build(),
+                Build::test()
+                    .id(1)
+                    .requester_name("admin")
+                    .status(BuildStatus::Successful)
+                    .build(),
+            ])
+            .unwrap();
+
+        let builds = store.get_all().unwrap();
+        assert_eq!(builds.len(), 2);
+        assert_eq!(builds[0].id, 2);
+        assert!(matches!(builds[0].status, BuildStatus::Failed));
+        assert_eq!(builds[1].requester_name.as_deref(), Some("admin"));
+        assert_eq!(store.get_last_notified_build_id("default"), Some(2));
+
+        store.clear().unwrap();
+        assert!(store.get_all().unwrap().is_empty());
+        assert_eq!(store.get_last_notified_build_id("default"), None);
+    }
+
+    #[test]
+    fn test_add_builds_upserts_by_id() {
+        let mut store = BuildStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        let changed = store
+            .add_builds(vec![
+                Build::test().id(1).status(BuildStatus::Successful).build(),
+                Build::test().id(2).status(BuildStatus::Running).build(),
+                Build::test().id(2).status(BuildStatus::Failed).build(),
+            ])
+            .unwrap();
+        assert_eq!(changed.len(), 2);
+        assert_eq!(changed[1].status, BuildStatus::Failed);
+
+        // build 1 is unchanged, build 2 is returned again with the same status
+        let changed = store
+            .add_builds(vec![
+                Build::test().id(1).status(BuildStatus::Successful).build(),
+                Build::test().id(2).status(BuildStatus::Failed).build(),
+            ])
+            .unwrap();
+        assert!(changed.is_empty());
+
+        let changed = store
+            .add_builds(vec![
+                Build::test().id(2).status(BuildStatus::Recommended).build(),
+            ])
+            .unwrap();
+        assert_eq!(changed.len(), 1);
+
+        let builds = store.get_all().unwrap();
+        assert_eq!(builds.len(), 2);
+        assert_eq!(builds[0].status, BuildStatus::Recommended);
+    }
+
+    #[test]
+    fn test_same_build_id_on_several_servers() {
+        let mut store = BuildStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        let infra = Build::test()
+            .id(1)
+            .server("infra")
+            .status(BuildStatus::Failed)
+            .build();
+        let changed = store
+            .add_builds(vec![
+                Build::test().id(1).status(BuildStatus::Successful).build(),
+                infra,
+            ])
+            .unwrap();
+        assert_eq!(changed.len(), 2);
+        assert_eq!(store.get_all().unwrap().len(), 2);
+        assert_eq!(store.get_last_notified_build_id("infra"), Some(1));
+
+        // configuration 1 of the infra server is seen for the first time
+        store
+            .track_transitions(&[Build::test().id(1).status(BuildStatus::Successful).build()])
+            .unwrap();
+        let transitions = store
+            .track_transitions(&[Build::test()
+                .id(2)
+                .server("infra")
+                .status(BuildStatus::Failed)
+                .build()])
+            .unwrap();
+        assert!(transitions.is_empty());
+
+        let query = BuildQuery {
+            server: Some("infra".to_string()),
+            ..Default::default()
+        };
+        let (builds, total) = store.query(&query).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(builds[0].status, BuildStatus::Failed);
+    }
+
+    #[test]
+    fn test_read_flags() {
+        let mut store = BuildStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        store
+            .add_builds(vec![
+                Build::test().id(1).status(BuildStatus::Successful).build(),
+                Build::test().id(2).status(BuildStatus::Running).build(),
+            ])
+            .unwrap();
+        assert_eq!(store.get_unread_count().unwrap(), 2);
+
+        assert!(store.set_read("default", 1, true).unwrap());
+        assert!(!store.set_read("default", 3, true).unwrap());
+        assert_eq!(store.get_unread_count().unwrap(), 1);
+        assert_eq!(store.mark_all_read().unwrap(), 1);
+        assert_eq!(store.get_unread_count().unwrap(), 0);
+
+        // saving a build again keeps it read, unless its status changed
+        store
+            .add_builds(vec![
+                Build::test().id(1).status(BuildStatus::Successful).build(),
+                Build::test().id(2).status(BuildStatus::Failed).build(),
+            ])
+            .unwrap();
+        let builds = store.get_all().unwrap();
+        assert!(!builds[0].read);
+        assert!(builds[1].read);
+    }
+
+    #[test]
+    fn test_retention_keeps_newest_builds() {
+        let mut store = BuildStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        store.retention = Retention {
+            max_rows: 2,
+            max_age_days: None,
+        };
+        store
+            .add_builds(
+                (1..=5)
+                    .map(|id| Build::test().id(id).status(BuildStatus::Successful).build())
+                    .collect(),
+            )
+            .unwrap();
+
+        let ids: Vec<i64> = store.get_all().unwrap().iter().map(|b| b.id).collect();
+        assert_eq!(ids, vec![5, 4]);
+    }
+
+    #[test]
+    fn test_retention_applies_to_each_server() {
+        let mut store = BuildStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        store.retention = Retention {
+            max_rows: 2,
+            max_age_days: None,
+        };
+        let now = OffsetDateTime::now_utc();
+        // the ids of the servers are unrelated, the begin date tells which builds are newer
+        let mut builds: Vec<Build> = (1..=3)
+            .map(|id| {
+                Build::test()
+                    .id(id)
+                    .begin_date(now - Duration::hours(10 - id))
+                    .build()
+            })
+            .collect();
+        builds.extend((1..=3).map(|hours| {
+            Build::test()
+                .id(1000 - hours)
+                .server("infra")
+                .begin_date(now - Duration::hours(hours))
+                .build()
+        }));
+        store.add_builds(builds).unwrap();
+
+        let builds: Vec<(String, i64)> = store
+            .get_all()
+            .unwrap()
+            .into_iter()
+            .map(|build| (build.server, build.id))
+            .collect();
+        assert_eq!(
+            builds,
+            vec![
+                ("infra".to_string(), 999),
+                ("infra".to_string(), 998),
+                ("default".to_string(), 3),
+                ("default".to_string(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_retention_trims_stored_builds() {
+        let mut store = BuildStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        store
+            .add_builds(
+                (1..=5)
+                    .map(|id| Build::test().id(id).status(BuildStatus::Successful).build())
+                    .collect(),
+            )
+            .unwrap();
+
+        store
+            .set_retention(Retention {
+                max_rows: 3,
+                max_age_days: None,
+            })
+            .unwrap();
+
+        let ids: Vec<i64> = store.get_all().unwrap().iter().map(|b| b.id).collect();
+        assert_eq!(ids, vec![5, 4, 3]);
+    }
+
+    #[test]
+    fn test_track_transitions() {
+        let mut store = BuildStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        let transitions = store
+            .track_transitions(&[
+                Build::test().id(1).status(BuildStatus::Successful).build(),
+                Build::test().id(2).status(BuildStatus::Failed).build(),
+            ])
+            .unwrap();
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].kind, TransitionKind::Broke);
+        assert_eq!(transitions[0].build.id, 2);
+
+        let transitions = store
+            .track_transitions(&[
+                Build::test().id(4).status(BuildStatus::Cancelled).build(),
+                Build::test().id(3).status(BuildStatus::Timeout).build(),
+                Build::test().id(5).status(BuildStatus::Failed).build(),
+                Build::test().id(2).status(BuildStatus::Successful).build(),
+            ])
+            .unwrap();
+        assert!(transitions.is_empty());
+
+        let transitions = store
+            .track_transitions(&[Build::test().id(6).status(BuildStatus::Recommended).build()])
+            .unwrap();
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].kind, TransitionKind::Fixed);
+        assert_eq!(transitions[0].failure_count, 3);
+    }
+
+    #[test]
+    fn test_first_build_of_configuration_is_no_transition() {
+        let mut store = BuildStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        let transitions = store
+            .track_transitions(&[Build::test().id(1).status(BuildStatus::Failed).build()])
+            .unwrap();
+        assert!(transitions.is_empty());
+    }
+
+    #[test]
+    fn test_to_path_glob() {
+        assert_eq!(to_path_glob("root/product"), "root/product*");
+        assert_eq!(to_path_glob("root/*/release"), "root/*/release");
+        assert_eq!(to_path_glob("root/[old]"), "root/[[]old]*");
+    }
+
+    #[test]
+    fn test_to_contains_pattern() {
+        assert_eq!(to_contains_pattern("1.0"), "%1.0%");
+        assert_eq!(to_contains_pattern("50%_a"), "%50\\%\\_a%");
+    }
+
+    #[test]
+    fn test_query_builds() {
+        let mut store = BuildStore::new(Database::open_in_memory().unwrap(), Retention::default());
+        store
+            .add_builds(
+                (1..=20)
+                    .map(|id| {
+                        let status = if id % 4 == 0 {
+                            BuildStatus::Failed
+                        } else {
+                            BuildStatus::Successful
+                        };
+                        Build::test()
+                            .id(id)
+                            .configuration_path(format!("root/app{}", id % 2))
+                            .requester_name("admin")
+                            .status(status)
+                            .build()
+                    })
+                    .collect(),
+            )
+            .unwrap();
+
+        let (builds, total) = store
+            .query(&BuildQuery {
+                statuses: vec![BuildStatus::Failed],
+                limit: 2,
+                ..BuildQuery::default()
+            })
+            .unwrap();
+        assert_eq!(total, 5);
+        assert_eq!(
+            builds.iter().map(|b| b.id).collect::<Vec<_>>(),
+            vec![20, 16]
+        );
+
+        let (builds, total) = store
+            .query(&BuildQuery {
+                configuration_path: Some("root/app1".to_string()),
+                sort_order: SortOrder::OldestFirst,
+                offset: 1,
+                limit: 3,
+                ..BuildQuery::default()
+            })
+            .unwrap();
+        assert_eq!(total, 10);
+        assert_eq!(
+            builds.iter().map(|b| b.id).collect::<Vec<_>>(),
+            vec![3, 5, 7]
+        );
+
+        let (builds, total) = store
+            .query(&BuildQuery {
+                version: Some("1.0.1".to_string()),
+                requester: Some("ADM".to_string()),
+                ..BuildQuery::default()
+            })
+            .unwrap();
+        assert_eq!(total, 11);
+        assert_eq!(builds[0].id, 19);
+    }
+}
@@ -0,0 +1,354 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+
+use crate::{constants::APP_ID, path};
+
+/// Account of the keyring entry used to check whether the keyring is available
+const PROBE_ACCOUNT: &str = "probe";
+/// Length of the AES-GCM nonce, in bytes
+const NONCE_LENGTH: usize = 12;
+
+/// Keeps the access tokens of the servers out of the settings store, by server name
+pub struct CredentialStore {
+    backend: Backend,
+    /// Backend chosen by an earlier launch, tokens only found there are moved to the current
+    /// backend, e.g. when the keyring was locked at some launch
+    fallback: Backend,
+    /// Tokens already read or written, so that the keyring is not queried on every settings
+    /// reload
+    cache: Mutex<BTreeMap<String, Option<String>>>,
+}
+
+enum Backend {
+    /// Keychain on macOS, Credential Manager on Windows, Secret Service on Linux
+    Keyring,
+    /// Tokens encrypted with a key of the config directory, when no keyring is available
+    EncryptedFile(EncryptedFile),
+}
+
+impl CredentialStore {
+    /// Uses the OS keyring when it is available, otherwise the encrypted file of the config
+    /// directory
+    pub fn init() -> Result<Self, String> {
+        let file = EncryptedFile::new(path::credentials_path()?, path::credentials_key_path()?);
+        let (backend, fallback) = match keyring_entry(PROBE_ACCOUNT).and_then(|entry| {
+            match entry.get_password() {
+                Ok(_) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            }
+        }) {
+            Ok(()) => {
+                tracing::info!("Storing access tokens in the OS keyring");
+                (Backend::Keyring, Backend::EncryptedFile(file))
+            }
+            Err(e) => {
+                tracing::warn!("OS keyring not available, storing access tokens encrypted: {e}");
+                (Backend::EncryptedFile(file), Backend::Keyring)
+            }
+        };
+
+        Ok(Self {
+            backend,
+            fallback,
+            cache: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    pub fn get_token(&self, server: &str) -> Result<Option<String>, String> {
+        let mut cache = self
+            .cache
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for credential cache: {e}"))?;
+        if let Some(token) = cache.get(server) {
+            return Ok(token.clone());
+        }
+
+        let mut token = self.backend.get(server)?;
+        if token.is_none() {
+            token = self.migrate_token(server);
+        }
+        cache.insert(server.to_string(), token.clone());
+        Ok(token)
+    }
+
+    pub fn set_token(&self, server: &str, token: &str) -> Result<(), String> {
+        if self.get_token(server)?.as_deref() == Some(token) {
+            return Ok(());
+        }
+
+        tracing::info!("Saving access token of {server}");
+        self.backend.set(server, token)?;
+        self.cache_token(server, Some(token.to_string()))
+    }
+
+    pub fn delete_token(&self, server: &str) -> Result<(), String> {
+        if self.get_token(server)?.is_none() {
+            return Ok(());
+        }
+
+        tracing::info!("Deleting access token of {server}");
+        self.backend.delete(server)?;
+        self.cache_token(server, None)
+    }
+
+    /// Moves the token saved by the other backend to the current one, the token stays where
+    /// it was when it cannot be moved. The other backend may well be unavailable, which only
+    /// means it has no token.
+    fn migrate_token(&self, server: &str) -> Option<String> {
+        let token = match self.fallback.get(server) {
+            Ok(token) => token?,
+            Err(e) => {
+                tracing::debug!("No access token of {server} to migrate: {e}");
+                return None;
+            }
+        };
+
+        tracing::info!("Moving access token of {server} to the current credential backend");
+        match self.backend.set(server, &token) {
+            Ok(()) => {
+                if let Err(e) = self.fallback.delete(server) {
+                    tracing::warn!("Failed to delete moved access token of {server}: {e}");
+                }
+            }
+            Err(e) => tracing::warn!("Failed to move access token of {server}: {e}"),
+        }
+        Some(token)
+    }
+
+    fn cache_token(&self, server: &str, token: Option<String>) -> Result<(), String> {
+        self.cache
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for credential cache: {e}"))?
+            .insert(server.to_string(), token);
+        Ok(())
+    }
+}
+
+impl Backend {
+    fn get(&self, server: &str) -> Result<Option<String>, String> {
+        match self {
+            Backend::Keyring => match keyring_entry(server)?.get_password() {
+                Ok(token) => Ok(Some(token)),
+                Err(keyring::Error::NoEntry) => Ok(None),
+                Err(e) => Err(format!("Failed to read token of {server}: {e}")),
+            },
+            Backend::EncryptedFile(file) => Ok(file.read()?.remove(server)),
+        }
+    }
+
+    fn set(&self, server: &str, token: &str) -> Result<(), String> {
+        match self {
+            Backend::Keyring => keyring_entry(server)?
+                .set_password(token)
+                .map_err(|e| format!("Failed to save token of {server}: {e}")),
+            Backend::EncryptedFile(file) => {
+                let mut tokens = file.read()?;
+                tokens.insert(server.to_string(), token.to_string());
+                file.write(&tokens)
+            }
+        }
+    }
+
+    fn delete(&self, server: &str) -> Result<(), String> {
+        match self {
+            Backend::Keyring => match keyring_entry(server)?.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(format!("Failed to delete token of {server}: {e}")),
+            },
+            Backend::EncryptedFile(file) => {
+                let mut tokens = file.read()?;
+                if tokens.remove(server).is_none() {
+                    return Ok(());
+                }
+                file.write(&tokens)
+            }
+        }
+    }
+}
+
+fn keyring_entry(server: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(APP_ID, server)
+        .map_err(|e| format!("Failed to open keyring entry of {server}: {e}"))
+}
+
+/// Content of the credentials file, the tokens by server name encrypted as JSON
+#[derive(Serialize, Deserialize)]
+struct EncryptedTokens {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Tokens encrypted with AES-256-GCM, the key is generated on first use and only readable by
+/// the user. The key is kept in plain text next to the credentials file, so this keeps the
+/// tokens out of the settings and their backups, not away from someone who can read the
+/// user's files.
+struct EncryptedFile {
+    path: PathBuf,
+    key_path: PathBuf,
+}
+
+impl EncryptedFile {
+    fn new(path: PathBuf, key_path: PathBuf) -> Self {
+        Self { path, key_path }
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, String>, String> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read credentials file: {e}"))?;
+        let encrypted: EncryptedTokens = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse credentials file: {e}"))?;
+        let nonce = STANDARD
+            .decode(&encrypted.nonce)
+            .map_err(|e| format!("Invalid nonce in credentials file: {e}"))?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err("Invalid nonce in credentials file".to_string());
+        }
+        let ciphertext = STANDARD
+            .decode(&encrypted.ciphertext)
+            .map_err(|e| format!("Invalid ciphertext in credentials file: {e}"))?;
+
+        let plaintext = self
+            .cipher()?
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| "Failed to decrypt credentials file, its key may have changed")?;
+        serde_json::from_slice(&plaintext)
+            .map_err(|e| format!("Failed to parse decrypted credentials: {e}"))
+    }
+
+    fn write(&self, tokens: &BTreeMap<String, String>) -> Result<(), String> {
+        let plaintext = serde_json::to_vec(tokens)
+            .map_err(|e| format!("Failed to serialize credentials: {e}"))?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()?
+            .encrypt(&nonce, plaintext.as_ref())
+            .map_err(|_| "Failed to encrypt credentials")?;
+
+        let encrypted = EncryptedTokens {
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+        let content = serde_json::to_string(&encrypted)
+            .map_err(|e| format!("Failed to serialize credentials file: {e}"))?;
+        write_private_file(&self.path, content.as_bytes())
+            .map_err(|e| format!("Failed to write credentials file: {e}"))
+    }
+
+    fn cipher(&self) -> Result<Aes256Gcm, String> {
+        if !self.key_path.exists() {
+            let key = Aes256Gcm::generate_key(OsRng);
+            write_private_file(&self.key_path, STANDARD.encode(key).as_bytes())
+                .map_err(|e| format!("Failed to write credentials key: {e}"))?;
+        }
+
+        let key = fs::read_to_string(&self.key_path)
+            .map_err(|e| format!("Failed to read credentials key: {e}"))
+            .and_then(|key| {
+                STANDARD
+                    .decode(key.trim())
+                    .map_err(|e| format!("Invalid credentials key: {e}"))
+            })?;
+        if key.len() != 32 {
+            return Err("Invalid credentials key".to_string());
+        }
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+    }
+}
+
+/// Writes the file, only readable and writable by the user on Unix
+fn write_private_file(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encrypted_file(name: &str) -> EncryptedFile {
+        let dir = std::env::temp_dir().join(format!("{APP_ID}-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = EncryptedFile::new(dir.join("credentials.json"), dir.join("credentials.key"));
+        let _ = fs::remove_file(&file.path);
+        let _ = fs::remove_file(&file.key_path);
+        file
+    }
+
+    #[test]
+    fn test_encrypted_file_round_trip() {
+        let file = encrypted_file("round-trip");
+        assert!(file.read().unwrap().is_empty());
+
+        let tokens = BTreeMap::from([
+            ("default".to_string(), "secret".to_string()),
+            ("staging".to_string(), "other secret".to_string()),
+        ]);
+        file.write(&tokens).unwrap();
+        assert_eq!(file.read().unwrap(), tokens);
+
+        let content = fs::read_to_string(&file.path).unwrap();
+        assert!(!content.contains("secret"));
+    }
+
+    #[test]
+    fn test_encrypted_file_with_another_key() {
+        let file = encrypted_file("another-key");
+        file.write(&BTreeMap::from([(
+            "default".to_string(),
+            "secret".to_string(),
+        )]))
+        .unwrap();
+
+        fs::remove_file(&file.key_path).unwrap();
+        assert!(file.read().is_err());
+    }
+
+    #[test]
+    fn test_token_moved_from_other_backend() {
+        let old_file = encrypted_file("migrate-old");
+        old_file
+            .write(&BTreeMap::from([(
+                "default".to_string(),
+                "secret".to_string(),
+            )]))
+            .unwrap();
+        let store = CredentialStore {
+            backend: Backend::EncryptedFile(encrypted_file("migrate-new")),
+            fallback: Backend::EncryptedFile(old_file),
+            cache: Mutex::new(BTreeMap::new()),
+        };
+
+        assert_eq!(
+            store.get_token("default").unwrap().as_deref(),
+            Some("secret")
+        );
+        assert_eq!(store.get_token("staging").unwrap(), None);
+        assert_eq!(
+            store.backend.get("default").unwrap().as_deref(),
+            Some("secret")
+        );
+        assert_eq!(store.fallback.get("default").unwrap(), None);
+    }
+}
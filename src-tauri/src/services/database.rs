@@ -0,0 +1,344 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use rusqlite::Connection;
+use time::{Duration, OffsetDateTime};
+
+use crate::constants::MAX_STORE_ROWS;
+
+/// Bump when the schema below changes, and add the migration to [`Database::migrate`]
+const SCHEMA_VERSION: i32 = 4;
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS builds (
+    server TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    configuration INTEGER NOT NULL,
+    configuration_path TEXT NOT NULL,
+    master_node_address TEXT NOT NULL,
+    requester INTEGER NOT NULL,
+    requester_name TEXT,
+    canceller INTEGER,
+    canceller_name TEXT,
+    version TEXT NOT NULL,
+    status TEXT NOT NULL,
+    begin_date INTEGER NOT NULL,
+    status_date INTEGER,
+    duration INTEGER NOT NULL,
+    wait_duration INTEGER NOT NULL,
+    read INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (server, id)
+);
+CREATE INDEX IF NOT EXISTS idx_builds_configuration ON builds (configuration);
+CREATE INDEX IF NOT EXISTS idx_builds_configuration_path ON builds (configuration_path);
+CREATE INDEX IF NOT EXISTS idx_builds_status ON builds (status);
+CREATE INDEX IF NOT EXISTS idx_builds_begin_date ON builds (begin_date);
+
+CREATE TABLE IF NOT EXISTS alerts (
+    server TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    subject TEXT NOT NULL,
+    priority TEXT NOT NULL,
+    category TEXT NOT NULL,
+    alert_message TEXT NOT NULL,
+    alert_trigger TEXT NOT NULL,
+    fixed INTEGER NOT NULL,
+    ctime INTEGER NOT NULL,
+    ack_time INTEGER NOT NULL,
+    read INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (server, id)
+);
+CREATE INDEX IF NOT EXISTS idx_alerts_ctime ON alerts (ctime);
+
+CREATE TABLE IF NOT EXISTS configuration_states (
+    server TEXT NOT NULL,
+    configuration INTEGER NOT NULL,
+    last_build_id INTEGER NOT NULL,
+    failure_count INTEGER NOT NULL,
+    failing_since INTEGER,
+    PRIMARY KEY (server, configuration)
+);
+"#;
+
+/// Moves the builds and alerts of the tables keyed by id only into the tables keyed by server
+/// and id, the rows of previous versions came from the single server now named `default`
+const MIGRATE_TO_SERVER_KEYS: &str = r#"
+ALTER TABLE builds RENAME TO builds_v3;
+ALTER TABLE alerts RENAME TO alerts_v3;
+DROP INDEX IF EXISTS idx_builds_configuration;
+DROP INDEX IF EXISTS idx_builds_configuration_path;
+DROP INDEX IF EXISTS idx_builds_status;
+DROP INDEX IF EXISTS idx_builds_begin_date;
+DROP INDEX IF EXISTS idx_alerts_ctime;
+DROP TABLE IF EXISTS configuration_states;
+"#;
+
+const COPY_TO_SERVER_KEYS: &str = r#"
+INSERT INTO builds (server, id, configuration, configuration_path, master_node_address,
+    requester, requester_name, canceller, canceller_name, version, status, begin_date,
+    status_date, duration, wait_duration, read)
+SELECT 'default', id, configuration, configuration_path, master_node_address, requester,
+    requester_name, canceller, canceller_name, version, status, begin_date, status_date,
+    duration, wait_duration, read
+FROM builds_v3;
+INSERT INTO alerts (server, id, subject, priority, category, alert_message, alert_trigger,
+    fixed, ctime, ack_time, read)
+SELECT 'default', id, subject, priority, category, alert_message, alert_trigger, fixed, ctime,
+    ack_time, read
+FROM alerts_v3;
+DROP TABLE builds_v3;
+DROP TABLE alerts_v3;
+"#;
+
+/// How much history the build and alert stores keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    /// Maximum number of rows of each server kept in each table, the newest ones are kept
+    pub max_rows: usize,
+    /// Rows older than this are removed, `None` keeps them regardless of age
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            max_rows: MAX_STORE_ROWS,
+            max_age_days: None,
+        }
+    }
+}
+
+impl Retention {
+    /// Rows created before the returned time (in unix millis) are expired, `None` when no
+    /// row is, including when the age reaches before the earliest supported date
+    pub fn cutoff_millis(&self, now: OffsetDateTime) -> Option<i64> {
+        self.max_age_days
+            .and_then(|days| now.checked_sub(Duration::days(days as i64)))
+            .map(to_millis)
+    }
+}
+
+/// Embedded SQLite database holding the build and alert history.
+///
+/// Cloning is cheap, all clones share the same connection.
+#[derive(Debug, Clone)]
+pub struct Database {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Database {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create database directory: {e}"))?;
+        }
+
+        tracing::info!("Opening history database {}", path.display());
+        let conn =
+            Connection::open(path).map_err(|e| format!("Failed to open history database: {e}"))?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(|e| format!("Failed to enable WAL mode: {e}"))?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, String> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open in-memory database: {e}"))?;
+        Self::init(conn)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self, String> {
+        Self::migrate(&mut conn)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Brings the schema to the current version, the database is left as it was when any step
+    /// fails
+    fn migrate(conn: &mut Connection) -> Result<(), String> {
+        let version: i32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(|e| format!("Failed to read database schema version: {e}"))?;
+
+        if version >= SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        tracing::info!("Migrating history database from version {version} to {SCHEMA_VERSION}");
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start history database migration: {e}"))?;
+        if (1..3).contains(&version) {
+            tx.execute_batch(
+                "ALTER TABLE builds ADD COLUMN read INTEGER NOT NULL DEFAULT 0;
+                 ALTER TABLE alerts ADD COLUMN read INTEGER NOT NULL DEFAULT 0;",
+            )
+            .map_err(|e| format!("Failed to add read flags to history database: {e}"))?;
+        }
+        if (1..4).contains(&version) {
+            // the health of the configurations is tracked again from the next builds
+            tx.execute_batch(MIGRATE_TO_SERVER_KEYS)
+                .map_err(|e| format!("Failed to key history database by server: {e}"))?;
+        }
+        tx.execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to create database schema: {e}"))?;
+        if (1..4).contains(&version) {
+            tx.execute_batch(COPY_TO_SERVER_KEYS)
+                .map_err(|e| format!("Failed to copy history to the server keyed tables: {e}"))?;
+        }
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(|e| format!("Failed to update database schema version: {e}"))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit history database migration: {e}"))
+    }
+
+    pub fn connection(&self) -> Result<MutexGuard<'_, Connection>, String> {
+        self.conn
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock for history database: {e}"))
+    }
+}
+
+pub fn to_millis(date_time: OffsetDateTime) -> i64 {
+    (date_time.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+pub fn from_millis(millis: i64) -> Result<OffsetDateTime, time::error::ComponentRange> {
+    OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn test_open_in_memory_creates_schema() {
+        let db = Database::open_in_memory().unwrap();
+        let conn = db.connection().unwrap();
+        let version: i32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+
+        let tables: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name IN ('builds', 'alerts')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 2);
+    }
+
+    #[test]
+    fn test_migrate_adds_read_flags_and_server_keys() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE builds (id INTEGER PRIMARY KEY, configuration INTEGER NOT NULL, \
+                 configuration_path TEXT NOT NULL, master_node_address TEXT NOT NULL, \
+                 requester INTEGER NOT NULL, requester_name TEXT, canceller INTEGER, \
+                 canceller_name TEXT, version TEXT NOT NULL, status TEXT NOT NULL, \
+                 begin_date INTEGER NOT NULL, status_date INTEGER, duration INTEGER NOT NULL, \
+                 wait_duration INTEGER NOT NULL);
+             CREATE INDEX idx_builds_status ON builds (status);
+             CREATE TABLE alerts (id INTEGER PRIMARY KEY, subject TEXT NOT NULL, \
+                 priority TEXT NOT NULL, category TEXT NOT NULL, alert_message TEXT NOT NULL, \
+                 alert_trigger TEXT NOT NULL, fixed INTEGER NOT NULL, ctime INTEGER NOT NULL, \
+                 ack_time INTEGER NOT NULL);
+             INSERT INTO builds VALUES (1, 1, 'root', 'localhost', 1, NULL, NULL, NULL, '1.0', \
+                 'FAILED', 0, NULL, 0, 0);
+             PRAGMA user_version = 2;",
+        )
+        .unwrap();
+
+        let db = Database::init(conn).unwrap();
+        let conn = db.connection().unwrap();
+        let (server, read): (String, bool) = conn
+            .query_row("SELECT server, read FROM builds WHERE id = 1", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(server, "default");
+        assert!(!read);
+        let version: i32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+
+        let indexes: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND tbl_name = 'builds' \
+                 AND name = 'idx_builds_status'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexes, 1);
+    }
+
+    #[test]
+    fn test_failed_migration_leaves_database_unchanged() {
+        // the builds of version 2 lack columns the copy to the server keyed tables needs
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE builds (id INTEGER PRIMARY KEY, version TEXT NOT NULL);
+             CREATE TABLE alerts (id INTEGER PRIMARY KEY, subject TEXT NOT NULL);
+             INSERT INTO builds VALUES (1, '1.0');
+             PRAGMA user_version = 2;",
+        )
+        .unwrap();
+
+        assert!(Database::migrate(&mut conn).is_err());
+        let version: i32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 2);
+        let columns: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('builds')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(columns, 2);
+        let version: String = conn
+            .query_row("SELECT version FROM builds WHERE id = 1", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(version, "1.0");
+    }
+
+    #[test]
+    fn test_millis_round_trip() {
+        let date_time = datetime!(2026-01-17 22:35:13.304 UTC);
+        assert_eq!(from_millis(to_millis(date_time)).unwrap(), date_time);
+    }
+
+    #[test]
+    fn test_retention_cutoff() {
+        let now = datetime!(2026-01-17 00:00:00 UTC);
+        assert_eq!(Retention::default().cutoff_millis(now), None);
+
+        let retention = Retention {
+            max_rows: 10,
+            max_age_days: Some(1),
+        };
+        assert_eq!(
+            retention.cutoff_millis(now),
+            Some(to_millis(datetime!(2026-01-16 00:00:00 UTC)))
+        );
+
+        let retention = Retention {
+            max_rows: 10,
+            max_age_days: Some(u32::MAX),
+        };
+        assert_eq!(retention.cutoff_millis(now), None);
+    }
+}
@@ -0,0 +1,158 @@
+//! Notifications of builds and alerts with their actions on desktop, where the notification
+//! plugin offers none. The actions performed on them are handled as the ones forwarded by the
+//! frontend on mobile.
+
+use tauri::AppHandle;
+
+use crate::{
+    commands::notifications::{handle_notification_action, send_native_notification},
+    types::notification::{NotificationAction, NotificationTarget},
+};
+
+/// Shows the notification, the notification itself opens its target and its buttons perform
+/// the other actions of the target
+#[cfg(target_os = "linux")]
+pub fn show_notification(
+    app: &AppHandle,
+    title: &str,
+    body: Option<String>,
+    target: NotificationTarget,
+) -> Result<(), String> {
+    let mut notification = notify_rust::Notification::new();
+    notification.summary(title).auto_icon();
+    if let Some(body) = &body {
+        notification.body(body);
+    }
+    // "default" is the action of a click on the notification itself
+    notification.action("default", "Open");
+    for (action, label) in target.desktop_actions() {
+        notification.action(&action.to_string(), label);
+    }
+
+    // waiting for the action blocks until the notification is closed
+    let app = app.clone();
+    std::thread::spawn(move || match notification.show() {
+        Ok(handle) => handle.wait_for_action(|action| {
+            let action = match action {
+                "default" => Some(NotificationAction::Open),
+                // "__closed" when the notification is dismissed
+                action => action.parse().ok(),
+            };
+            if let Some(action) = action {
+                perform_action(app, action, target);
+            }
+        }),
+        Err(e) => tracing::error!("Failed to send native notification: {e}"),
+    });
+    Ok(())
+}
+
+/// Shows the notification, the notification itself opens its target and its button performs
+/// the other action of the target
+#[cfg(target_os = "macos")]
+pub fn show_notification(
+    app: &AppHandle,
+    title: &str,
+    body: Option<String>,
+    target: NotificationTarget,
+) -> Result<(), String> {
+    use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+
+    use crate::constants::APP_ID;
+
+    let app = app.clone();
+    let title = title.to_string();
+    // waiting for the click blocks until the notification is closed
+    std::thread::spawn(move || {
+        // fails once the application is set, as done by the notification plugin as well
+        let _ = mac_notification_sys::set_application(if tauri::is_dev() {
+            "com.apple.Terminal"
+        } else {
+            APP_ID
+        });
+
+        let actions = target.desktop_actions();
+        let mut notification = Notification::new();
+        notification.title(&title).wait_for_click(true);
+        if let Some(body) = &body {
+            notification.message(body);
+        }
+        if let Some((_, label)) = actions.first() {
+            notification.main_button(MainButton::SingleAction(label));
+        }
+
+        let action = match notification.send() {
+            Ok(NotificationResponse::Click) => Some(NotificationAction::Open),
+            Ok(NotificationResponse::ActionButton(clicked)) => actions
+                .iter()
+                .find(|(_, label)| *label == clicked)
+                .map(|(action, _)| *action),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::error!("Failed to send native notification: {e}");
+                None
+            }
+        };
+        if let Some(action) = action {
+            perform_action(app, action, target);
+        }
+    });
+    Ok(())
+}
+
+/// Shows the notification, the notification itself opens its target and its buttons perform
+/// the other actions of the target. Clicks are only reported while the app is running.
+#[cfg(target_os = "windows")]
+pub fn show_notification(
+    app: &AppHandle,
+    title: &str,
+    body: Option<String>,
+    target: NotificationTarget,
+) -> Result<(), String> {
+    use tauri_winrt_notification::Toast;
+
+    use crate::constants::APP_ID;
+
+    // the app id is only registered by the installer, as assumed by the notification plugin
+    let app_id = if cfg!(debug_assertions) {
+        Toast::POWERSHELL_APP_ID
+    } else {
+        APP_ID
+    };
+    let mut toast = Toast::new(app_id).title(title);
+    if let Some(body) = &body {
+        toast = toast.text1(body);
+    }
+    for (action, label) in target.desktop_actions() {
+        toast = toast.add_button(label, &action.to_string());
+    }
+
+    let app = app.clone();
+    toast
+        .on_activated(move |action| {
+            // no action is given when the notification itself is clicked
+            let action = match action {
+                Some(action) => action.parse().ok(),
+                None => Some(NotificationAction::Open),
+            };
+            if let Some(action) = action {
+                perform_action(app.clone(), action, target.clone());
+            }
+            Ok(())
+        })
+        .show()
+        .map_err(|e| format!("Failed to send notification: {e}"))
+}
+
+/// Performs the action in the background, failures are told by another notification as the
+/// app may not be visible
+fn perform_action(app: AppHandle, action: NotificationAction, target: NotificationTarget) {
+    tracing::info!("Notification action {action} performed on {target:?}");
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = handle_notification_action(app.clone(), action, target).await {
+            tracing::error!("Failed to handle notification action {action}: {e}");
+            let title = format!("Failed to {}", action.to_string().replace('_', " "));
+            let _ = send_native_notification(app, title, Some(e), None).await;
+        }
+    });
+}
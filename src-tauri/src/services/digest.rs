@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    constants::MAX_DIGEST_LINES,
+    types::{
+        alert::Alert,
+        build::{Build, BuildStatus},
+    },
+};
+
+/// Builds and alerts accumulated over the digest window, notified as a single summary
+#[derive(Debug, Default)]
+pub struct Digest {
+    builds: Vec<Build>,
+    alerts: Vec<Alert>,
+    /// When the first build or alert of the digest was added
+    started: Option<OffsetDateTime>,
+}
+
+/// Counts of the builds of a configuration by outcome
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct BuildCounts {
+    failed: usize,
+    successful: usize,
+    cancelled: usize,
+}
+
+impl BuildCounts {
+    fn add(&mut self, status: &BuildStatus) {
+        if status.is_failure() {
+            self.failed += 1;
+        } else if status.is_success() {
+            self.successful += 1;
+        } else if *status == BuildStatus::Cancelled {
+            self.cancelled += 1;
+        }
+    }
+
+    fn merge(&mut self, other: BuildCounts) {
+        self.failed += other.failed;
+        self.successful += other.successful;
+        self.cancelled += other.cancelled;
+    }
+
+    /// e.g. `2 failed, 1 successful`, outcomes without builds are left out
+    fn describe(&self) -> String {
+        [
+            (self.failed, "failed"),
+            (self.successful, "successful"),
+            (self.cancelled, "cancelled"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, outcome)| format!("{count} {outcome}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+impl Digest {
+    pub fn is_empty(&self) -> bool {
+        self.builds.is_empty() && self.alerts.is_empty()
+    }
+
+    /// Adds the finished builds, running builds are summarized once they finish as they are
+    /// fetched again then
+    pub fn add_builds(&mut self, builds: Vec<Build>, now: OffsetDateTime) {
+        let builds: Vec<Build> = builds
+            .into_iter()
+            .filter(|build| build.status != BuildStatus::Running)
+            .collect();
+        if builds.is_empty() {
+            return;
+        }
+        self.started = self.started.or(Some(now));
+        self.builds.extend(builds);
+    }
+
+    pub fn add_alerts(&mut self, alerts: Vec<Alert>, now: OffsetDateTime) {
+        if alerts.is_empty() {
+            return;
+        }
+        self.started = self.started.or(Some(now));
+        self.alerts.extend(alerts);
+    }
+
+    /// Whether the window started by the first build or alert of the digest has elapsed
+    pub fn is_due(&self, now: OffsetDateTime, window: Duration) -> bool {
+        self.started.is_some_and(|started| now - started >= window)
+    }
+
+    /// Empties the digest, returns the title and body of its summary
+    pub fn take_summary(&mut self) -> Option<(String, String)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let digest = std::mem::take(self);
+        Some(summarize(&digest.builds, &digest.alerts))
+    }
+}
+
+fn summarize(builds: &[Build], alerts: &[Alert]) -> (String, String) {
+    let mut configurations = BTreeMap::<&str, BuildCounts>::new();
+    for build in builds {
+        configurations
+            .entry(build.configuration_path.as_str())
+            .or_default()
+            .add(&build.status);
+    }
+
+    let mut total = BuildCounts::default();
+    configurations
+        .values()
+        .for_each(|counts| total.merge(*counts));
+
+    let mut title = Vec::new();
+    if !builds.is_empty() {
+        let builds_text = match builds.len() {
+            1 => "1 build".to_string(),
+            len => format!("{len} builds"),
+        };
+        title.push(format!("{builds_text}: {}", total.describe()));
+    }
+    if !alerts.is_empty() {
+        title.push(match alerts.len() {
+            1 => "1 alert".to_string(),
+            len => format!("{len} alerts"),
+        });
+    }
+
+    // failing configurations first, they are the ones worth a look
+    let mut lines: Vec<(&str, BuildCounts)> = configurations.into_iter().collect();
+    lines.sort_by_key(|(_, counts)| std::cmp::Reverse(counts.failed));
+    let mut body: Vec<String> = lines
+        .iter()
+        .map(|(path, counts)| format!("{path}: {}", counts.describe()))
+        .chain(alerts.iter().map(|alert| alert.subject.clone()))
+        .collect();
+    if body.len() > MAX_DIGEST_LINES {
+        let more = body.len() - MAX_DIGEST_LINES;
+        body.truncate(MAX_DIGEST_LINES);
+        body.push(format!(" and {more} more ..."));
+    }
+
+    (title.join(", "), body.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn test_digest_window() {
+        let mut digest = Digest::default();
+        let now = datetime!(2026-01-17 10:00 UTC);
+        assert!(!digest.is_due(now, Duration::minutes(15)));
+
+        digest.add_builds(
+            vec![Build::test().id(1).status(BuildStatus::Failed).build()],
+            now,
+        );
+        digest.add_builds(
+            vec![Build::test().id(2).status(BuildStatus::Failed).build()],
+            now + Duration::minutes(10),
+        );
+        assert!(!digest.is_due(now + Duration::minutes(14), Duration::minutes(15)));
+        assert!(digest.is_due(now + Duration::minutes(15), Duration::minutes(15)));
+
+        assert!(digest.take_summary().is_some());
+        assert!(digest.is_empty());
+        assert!(!digest.is_due(now + Duration::minutes(30), Duration::minutes(15)));
+    }
+
+    #[test]
+    fn test_summary_groups_by_configuration() {
+        let mut digest = Digest::default();
+        let now = datetime!(2026-01-17 10:00 UTC);
+        digest.add_builds(
+            vec![
+                Build::test().id(1).status(BuildStatus::Successful).build(),
+                Build::test()
+                    .id(2)
+                    .configuration_path("root/lib")
+                    .status(BuildStatus::Failed)
+                    .build(),
+                Build::test().id(3).status(BuildStatus::Recommended).build(),
+                Build::test()
+                    .id(4)
+                    .configuration_path("root/lib")
+                    .status(BuildStatus::Cancelled)
+                    .build(),
+                Build::test()
+                    .id(5)
+                    .configuration_path("root/lib")
+                    .status(BuildStatus::Timeout)
+                    .build(),
+            ],
+            now,
+        );
+        digest.add_alerts(
+            vec![Alert::test().subject("Disk space is low").build()],
+            now,
+        );
+
+        let (title, body) = digest.take_summary().unwrap();
+        assert_eq!(
+            title,
+            "5 builds: 2 failed, 2 successful, 1 cancelled, 1 alert"
+        );
+        assert_eq!(
+            body,
+            "root/lib: 2 failed, 1 cancelled\nroot/app: 2 successful\nDisk space is low"
+        );
+    }
+
+    #[test]
+    fn test_running_builds_are_left_out() {
+        let mut digest = Digest::default();
+        let now = datetime!(2026-01-17 10:00 UTC);
+        digest.add_builds(
+            vec![Build::test().id(1).status(BuildStatus::Running).build()],
+            now,
+        );
+        assert!(digest.is_empty());
+        assert!(!digest.is_due(now + Duration::minutes(15), Duration::minutes(15)));
+
+        digest.add_builds(
+            vec![
+                Build::test().id(1).status(BuildStatus::Failed).build(),
+                Build::test().id(2).status(BuildStatus::Running).build(),
+            ],
+            now,
+        );
+        let (title, body) = digest.take_summary().unwrap();
+        assert_eq!(title, "1 build: 1 failed");
+        assert_eq!(body, "root/app: 1 failed");
+    }
+}
@@ -1,4 +1,12 @@
 pub mod alert_store;
+pub mod backoff;
 pub mod build_store;
+pub mod credentials;
+pub mod database;
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+pub mod desktop_notifications;
+pub mod digest;
 pub mod poll;
 pub mod quickbuild;
+pub mod rules;
+pub mod watch;
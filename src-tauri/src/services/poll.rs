@@ -1,14 +1,159 @@
-use std::{sync::Mutex, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::Duration,
+};
 
 use tauri::{AppHandle, Emitter, Manager, Wry};
 use time::OffsetDateTime;
-use tokio::time::sleep;
+use tokio::{
+    sync::{Mutex as AsyncMutex, watch},
+    task::JoinHandle,
+    time::{sleep, timeout},
+};
 
 use crate::{
-    AppState, commands::notifications::send_native_notification,
-    services::quickbuild::QuickBuildClient, types::settings::AppSettings,
+    AppState, ServerState,
+    commands::{
+        notifications::{send_native_notification, send_quiet_hours_digest},
+        settings::set_paused,
+    },
+    constants::MAX_POLL_BACKOFF_SECS,
+    services::{
+        backoff::Backoff,
+        digest::Digest,
+        quickbuild::{QuickBuildClient, QuickBuildError},
+        rules::RuleEngine,
+        watch::WatchMatcher,
+    },
+    tray::{refresh_tray_menu, refresh_tray_status},
+    types::{
+        alert::Alert,
+        build::{Build, BuildStatus},
+        notification::NotificationTarget,
+        rule::RuleAction,
+        server::{ServerProfile, ServerStatus},
+        settings::AppSettings,
+        watch::ConfigurationStatus,
+    },
 };
 
+/// How often the scheduler starts the pollers of new servers and sends the due digests
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(10);
+/// Longest wait for the servers to be polled on demand
+const POLL_NOW_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Wakes the scheduler and the pollers up before their next poll, so that they pick up the
+/// changed settings or poll right away. A wake up sent while they are polling is kept until
+/// they wait again.
+pub struct PollSignal {
+    sender: watch::Sender<()>,
+    /// Notified whenever a poller completes a poll
+    completed: watch::Sender<()>,
+    /// Serializes the on-demand polls, holds when the last one completed and its result
+    last_poll_now: AsyncMutex<Option<(OffsetDateTime, Vec<ServerStatus>)>>,
+}
+
+impl PollSignal {
+    pub fn new() -> Self {
+        Self {
+            sender: watch::channel(()).0,
+            completed: watch::channel(()).0,
+            last_poll_now: AsyncMutex::new(None),
+        }
+    }
+
+    pub fn wake_up(&self) {
+        self.sender.send_replace(());
+    }
+
+    fn subscribe(&self) -> watch::Receiver<()> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for PollSignal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Wakes the scheduler and the pollers of the app up
+pub fn wake_up(app: &AppHandle<Wry>) {
+    app.state::<PollSignal>().wake_up();
+}
+
+/// Polls every server right away, and returns their status once polled. Requests made while
+/// an on-demand poll is in progress get its result instead of polling again.
+pub async fn poll_now(app: &AppHandle<Wry>) -> Result<Vec<ServerStatus>, String> {
+    let requested_time = OffsetDateTime::now_utc();
+    let signal = app.state::<PollSignal>();
+    let mut last_poll_now = signal.last_poll_now.lock().await;
+    if let Some((completed_time, servers)) = last_poll_now.as_ref()
+        && *completed_time >= requested_time
+    {
+        tracing::debug!("Servers were polled on demand in the meantime");
+        return Ok(servers.clone());
+    }
+
+    let state = app.state::<Mutex<AppState>>();
+    let settings = get_settings(&state)?;
+    if !settings.is_configured() {
+        return Err("QuickBuild server is not configured".to_string());
+    }
+    if settings.paused {
+        return Err("Polling is paused, resume it to refresh".to_string());
+    }
+
+    tracing::info!("Polling the servers on demand");
+    let mut completed = signal.completed.subscribe();
+    let start_time = OffsetDateTime::now_utc();
+    signal.wake_up();
+    let polled = timeout(POLL_NOW_TIMEOUT, async {
+        while !is_polled_since(&state, &settings, start_time) {
+            if completed.changed().await.is_err() {
+                return false;
+            }
+        }
+        true
+    })
+    .await;
+    if !matches!(polled, Ok(true)) {
+        return Err("Timed out waiting for the servers to be polled".to_string());
+    }
+
+    let servers = state
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock for getting server statuses: {e}"))?
+        .get_server_statuses(ServerState::get_polling_error);
+    *last_poll_now = Some((OffsetDateTime::now_utc(), servers.clone()));
+    Ok(servers)
+}
+
+/// Whether every server of the settings completed a poll started after the given time
+fn is_polled_since(state: &Mutex<AppState>, settings: &AppSettings, time: OffsetDateTime) -> bool {
+    let Ok(state_guard) = state.lock() else {
+        return false;
+    };
+    settings.get_servers().iter().all(|server| {
+        state_guard
+            .servers
+            .get(&server.name)
+            .and_then(|server_state| server_state.last_poll_start_time)
+            .is_some_and(|start_time| start_time >= time)
+    })
+}
+
+/// Sleeps for the given duration, or until woken up by the [`PollSignal`]
+async fn sleep_or_wake_up(duration: Duration, signal: &mut watch::Receiver<()>) {
+    tokio::select! {
+        _ = sleep(duration) => {}
+        Ok(()) = signal.changed() => {
+            tracing::debug!("Woken up before the next poll");
+        }
+    }
+}
+
 fn get_settings(state: &Mutex<AppState>) -> Result<AppSettings, String> {
     let state_guard = state
         .lock()
@@ -19,39 +164,119 @@ fn get_settings(state: &Mutex<AppState>) -> Result<AppSettings, String> {
 pub async fn start(app: AppHandle<Wry>) {
     tracing::info!("Starting scheduler service");
     let state = app.state::<Mutex<AppState>>();
+    let mut signal = app.state::<PollSignal>().subscribe();
+    let mut pollers = HashMap::<String, JoinHandle<()>>::new();
 
     loop {
         let settings = match get_settings(&state) {
             Ok(settings) => settings,
             Err(e) => {
                 tracing::error!("Failed to get settings: {e}");
-                sleep(Duration::from_secs(10)).await;
+                sleep_or_wake_up(SCHEDULER_INTERVAL, &mut signal).await;
                 continue;
             }
         };
 
+        if let Err(e) = send_quiet_hours_digest(&app) {
+            tracing::error!("Failed to send quiet hours digest: {e}");
+        }
+        send_digest(&app, &state).await;
+
+        if settings.is_pause_expired(OffsetDateTime::now_utc()) {
+            tracing::info!("Pause has expired, resuming polling");
+            if let Err(e) = set_paused(&app, false, None) {
+                tracing::error!("Failed to resume polling: {e}");
+                sleep_or_wake_up(SCHEDULER_INTERVAL, &mut signal).await;
+            }
+            continue;
+        }
+
+        // each enabled server has its own poller, which stops once the server is removed or
+        // disabled
+        pollers.retain(|_, poller| !poller.is_finished());
+        for server in settings.get_servers() {
+            if !pollers.contains_key(&server.name) {
+                tracing::info!("Starting poller of server {}", server.name);
+                let poller = tokio::spawn(poll_server(app.clone(), server.name.clone()));
+                pollers.insert(server.name, poller);
+            }
+        }
+
         if !settings.is_configured() {
             tracing::debug!("QuickBuild settings not configured, skipping fetching notifications");
-            sleep(Duration::from_secs(10)).await;
-            continue;
+            refresh_tray_status(&app);
+        } else if settings.paused {
+            refresh_tray_status(&app);
         }
 
+        sleep_or_wake_up(SCHEDULER_INTERVAL, &mut signal).await;
+    }
+}
+
+/// Polls the builds and alerts of the server, until it is removed or disabled in the settings
+async fn poll_server(app: AppHandle<Wry>, server: String) {
+    let state = app.state::<Mutex<AppState>>();
+    let poll_signal = app.state::<PollSignal>();
+    let mut signal = poll_signal.subscribe();
+    let mut backoff = Backoff::new(Duration::from_secs(MAX_POLL_BACKOFF_SECS));
+    // kept while the profile is unchanged, so that the id of the user is only resolved once
+    let mut client: Option<(ServerProfile, QuickBuildClient)> = None;
+
+    loop {
+        let settings = match get_settings(&state) {
+            Ok(settings) => settings,
+            Err(e) => {
+                tracing::error!("Failed to get settings: {e}");
+                sleep_or_wake_up(SCHEDULER_INTERVAL, &mut signal).await;
+                continue;
+            }
+        };
+
+        let Ok(profile) = settings.get_server(&server) else {
+            tracing::info!("Server {server} was removed or disabled, stopping its poller");
+            return;
+        };
+
         let poll_interval = Duration::from_secs(settings.poll_interval_in_secs as u64);
         if settings.paused {
-            tracing::debug!("Polling is paused, skipping fetching notifications");
-            sleep(poll_interval).await;
+            tracing::debug!("Polling is paused, skipping fetching notifications of {server}");
+            sleep_or_wake_up(poll_interval, &mut signal).await;
             continue;
         }
 
-        // Create QuickBuild service
-        let client = QuickBuildClient::builder()
-            .host(settings.server_url.clone())
-            .user(settings.user.clone())
-            .token(settings.token.clone())
-            .build();
+        if client
+            .as_ref()
+            .is_some_and(|(cached, _)| *cached != profile)
+        {
+            client = None;
+        }
+        let (_, client) = client
+            .get_or_insert_with(|| (profile.clone(), QuickBuildClient::from_profile(&profile)));
+        let start_time = OffsetDateTime::now_utc();
+
+        let builds_result = fetch_builds(client, app.clone(), &state).await;
+        let alerts_result = fetch_alerts(client, app.clone(), &state).await;
+        // the server failing the poll would fail each status check as well
+        let statuses_result = if builds_result.is_ok() && alerts_result.is_ok() {
+            fetch_configuration_statuses(client, &app, &state).await
+        } else {
+            Ok(())
+        };
 
-        let _ = fetch_builds(&client, app.clone(), &state).await;
-        let _ = fetch_alerts(&client, app.clone(), &state).await;
+        if builds_result.is_ok() && alerts_result.is_ok() && statuses_result.is_ok() {
+            backoff.record_success();
+        } else {
+            backoff.record_failure();
+        }
+
+        let delay = backoff.next_delay(poll_interval);
+        if backoff.consecutive_failures() > 0 {
+            tracing::warn!(
+                "Polling {server} failed {} times in a row, retrying in {:?}",
+                backoff.consecutive_failures(),
+                delay
+            );
+        }
 
         {
             let mut state_guard = match state.lock() {
@@ -62,74 +287,245 @@ pub async fn start(app: AppHandle<Wry>) {
                 }
             };
 
-            state_guard.last_polling_time = Some(OffsetDateTime::now_utc());
+            let now = OffsetDateTime::now_utc();
+            let server_state = state_guard.server_state_mut(&server);
+            server_state.last_polling_time = Some(now);
+            server_state.last_poll_start_time = Some(start_time);
+            server_state.next_polling_time = Some(now + delay);
+            server_state.consecutive_failures = backoff.consecutive_failures();
         }
 
-        sleep(poll_interval).await;
+        poll_signal.completed.send_replace(());
+        refresh_tray_status(&app);
+
+        sleep_or_wake_up(delay, &mut signal).await;
     }
 }
 
-const POLLING_FAILED_MESSAGE: &str = "Polling failed, please check your connection and try again";
+/// Adds the items to the digest when digests are enabled, returns the items to notify right away
+fn collect_into_digest<T>(
+    state: &Mutex<AppState>,
+    items: Vec<T>,
+    add: impl FnOnce(&mut Digest, Vec<T>, OffsetDateTime),
+) -> Vec<T> {
+    let mut state = state.lock().unwrap();
+    if items.is_empty() || state.settings.get_digest_window().is_none() {
+        return items;
+    }
+
+    add(&mut state.digest, items, OffsetDateTime::now_utc());
+    Vec::new()
+}
 
-async fn fetch_builds(client: &QuickBuildClient, app: AppHandle<Wry>, state: &Mutex<AppState>) {
-    let last_notified_build_id = { state.lock().unwrap().get_last_notified_build_id() };
-    let old_error = { state.lock().unwrap().build_polling_error.clone() };
+/// Notifies the summary of the digest once its window has elapsed, or right away when digests
+/// were disabled in the meantime
+async fn send_digest(app: &AppHandle<Wry>, state: &Mutex<AppState>) {
+    let summary = {
+        let mut state = state.lock().unwrap();
+        let is_due = match state.settings.get_digest_window() {
+            Some(window) => state.digest.is_due(OffsetDateTime::now_utc(), window),
+            None => true,
+        };
+        if !is_due {
+            return;
+        }
+        state.digest.take_summary()
+    };
+
+    if let Some((title, body)) = summary {
+        tracing::info!("Sending digest notification: {title}");
+        let _ = send_native_notification(app.clone(), title, Some(body), None).await;
+    }
+}
+
+async fn fetch_builds(
+    client: &QuickBuildClient,
+    app: AppHandle<Wry>,
+    state: &Mutex<AppState>,
+) -> Result<(), QuickBuildError> {
+    let server = client.server();
+    let last_notified_build_id = { state.lock().unwrap().get_last_notified_build_id(server) };
+    let old_error = {
+        let state = state.lock().unwrap();
+        state
+            .servers
+            .get(server)
+            .and_then(|state| state.build_polling_error.clone())
+    };
     let should_refresh;
 
-    match client.get_builds(last_notified_build_id).await {
+    // rules of the builds requested by the user need the id of the user
+    if let Err(e) = client.get_user_id().await {
+        tracing::warn!("Failed to resolve the user id on {server}: {e}");
+    }
+
+    let result = match client.get_builds(last_notified_build_id).await {
         Ok(builds) => {
+            tracing::debug!("{} builds fetched successfully", builds.len());
+            let (builds, transitions, changed) = {
+                let mut state = state.lock().unwrap();
+                let rules = RuleEngine::from_settings(&state.settings, client.user_id());
+                let watch = WatchMatcher::from_settings(&state.settings);
+                if let Some(id) = builds.iter().map(|build| build.id).max() {
+                    let server_state = state.server_state_mut(server);
+                    server_state.last_fetched_build_id =
+                        server_state.last_fetched_build_id.max(Some(id));
+                }
+
+                // only builds that are new or changed status are notified
+                let builds = state
+                    .add_builds(rules.retain_builds(builds))
+                    .unwrap_or_else(|e| {
+                        tracing::error!("Failed to save builds: {e}");
+                        Vec::new()
+                    });
+                let transitions = state.track_build_transitions(&builds).unwrap_or_else(|e| {
+                    tracing::error!("Failed to track build transitions: {e}");
+                    Vec::new()
+                });
+                let changed = !builds.is_empty();
+                let builds: Vec<Build> = builds
+                    .into_iter()
+                    .filter(|build| {
+                        rules.build_action(build) == RuleAction::Notify
+                            && watch.should_notify(build)
+                    })
+                    .collect();
+                (builds, transitions, changed)
+            };
+
+            // builds breaking or fixing their configuration are notified on their own
+            for transition in &transitions {
+                tracing::info!("Build transition: {}", transition.get_subject());
+                let _ = app.emit("build-transition", transition);
+                if builds.iter().any(|build| build.id == transition.build.id) {
+                    let title = transition.get_subject();
+                    let body = transition.build.get_body().ok();
+                    let target = NotificationTarget::build(&transition.build);
+                    let _ = send_native_notification(app.clone(), title, body, Some(target)).await;
+                }
+            }
+            let builds: Vec<Build> = builds
+                .into_iter()
+                .filter(|build| {
+                    !transitions
+                        .iter()
+                        .any(|transition| transition.build.id == build.id)
+                })
+                .collect();
+
+            let builds = collect_into_digest(state, builds, |digest, builds, now| {
+                digest.add_builds(builds, now)
+            });
+
             let len = builds.len();
             if len > 0 {
-                tracing::debug!("{} builds fetched successfully", len);
                 if len == 1 {
                     let build = &builds[0];
                     let title = build.get_subject();
                     let body = build.get_body().unwrap();
-                    let _ = send_native_notification(app.clone(), title, Some(body)).await;
+                    let target = NotificationTarget::build(build);
+                    let _ = send_native_notification(app.clone(), title, Some(body), Some(target))
+                        .await;
                 } else {
                     let title = format!("{} new builds are finished", len);
-                    let _ = send_native_notification(app.clone(), title, None).await;
+                    let _ = send_native_notification(app.clone(), title, None, None).await;
                 }
+            }
 
-                let _ = state.lock().unwrap().add_builds(builds);
+            if changed {
+                refresh_tray_menu(&app);
             }
 
-            should_refresh = len > 0 || old_error.is_some();
-            state.lock().unwrap().build_polling_error = None;
+            should_refresh = changed || old_error.is_some();
+            state
+                .lock()
+                .unwrap()
+                .server_state_mut(server)
+                .build_polling_error = None;
+            Ok(())
         }
         Err(e) => {
-            tracing::error!("Failed to get builds: {e}");
+            tracing::error!("Failed to get builds of {server}: {e}");
             tracing::info!("Old error: {old_error:?}");
-            should_refresh = old_error != Some(POLLING_FAILED_MESSAGE.to_string());
-            state.lock().unwrap().build_polling_error = Some(POLLING_FAILED_MESSAGE.to_string());
+            should_refresh = old_error.as_ref() != Some(&e);
+            state
+                .lock()
+                .unwrap()
+                .server_state_mut(server)
+                .build_polling_error = Some(e.clone());
+            Err(e)
         }
-    }
+    };
 
     if should_refresh {
         tracing::info!("Emitting builds-refresh-page event");
         let _ = app.emit("builds-refresh-page", ());
     }
+
+    result
 }
 
-async fn fetch_alerts(client: &QuickBuildClient, app: AppHandle<Wry>, state: &Mutex<AppState>) {
-    let last_notified_time = { state.lock().unwrap().get_last_notified_time() };
-    let old_error = { state.lock().unwrap().alert_polling_error.clone() };
+async fn fetch_alerts(
+    client: &QuickBuildClient,
+    app: AppHandle<Wry>,
+    state: &Mutex<AppState>,
+) -> Result<(), QuickBuildError> {
+    let server = client.server();
+    let last_notified_time = { state.lock().unwrap().get_last_notified_time(server) };
+    let old_error = {
+        let state = state.lock().unwrap();
+        state
+            .servers
+            .get(server)
+            .and_then(|state| state.alert_polling_error.clone())
+    };
     let should_refresh;
 
     tracing::info!(
-        "Fetching alerts with last notified time: {:?}",
+        "Fetching alerts of {server} with last notified time: {:?}",
         last_notified_time
     );
-    match client.get_alerts(last_notified_time).await {
+    let result = match client.get_alerts(last_notified_time).await {
         Ok(alerts) => {
+            tracing::debug!("{} alerts fetched successfully", alerts.len());
+            let (alerts, changed) = {
+                let mut state = state.lock().unwrap();
+                let rules = RuleEngine::from_settings(&state.settings, client.user_id());
+                if let Some(ctime) = alerts.iter().map(|alert| alert.ctime).max() {
+                    let server_state = state.server_state_mut(server);
+                    server_state.last_fetched_alert_time =
+                        server_state.last_fetched_alert_time.max(Some(ctime));
+                }
+
+                // only alerts that are new or were fixed or acknowledged are notified
+                let alerts = state
+                    .add_alerts(rules.retain_alerts(alerts))
+                    .unwrap_or_else(|e| {
+                        tracing::error!("Failed to save alerts: {e}");
+                        Vec::new()
+                    });
+                let changed = !alerts.is_empty();
+                let alerts: Vec<Alert> = alerts
+                    .into_iter()
+                    .filter(|alert| rules.alert_action(alert) == RuleAction::Notify)
+                    .collect();
+                (alerts, changed)
+            };
+
+            let alerts = collect_into_digest(state, alerts, |digest, alerts, now| {
+                digest.add_alerts(alerts, now)
+            });
+
             let len = alerts.len();
             if len > 0 {
-                tracing::debug!("{} alerts fetched successfully", len);
                 if len == 1 {
                     let alert = &alerts[0];
                     let title = alert.subject.clone();
                     let body = alert.alert_message.clone();
-                    let _ = send_native_notification(app.clone(), title, Some(body)).await;
+                    let target = NotificationTarget::alert(alert);
+                    let _ = send_native_notification(app.clone(), title, Some(body), Some(target))
+                        .await;
                 } else {
                     let alert = &alerts[0];
                     let title = format!("{} and more alerts ...", alert.subject);
@@ -138,25 +534,215 @@ async fn fetch_alerts(client: &QuickBuildClient, app: AppHandle<Wry>, state: &Mu
                         alert.alert_message.clone(),
                         len - 1
                     );
-                    let _ = send_native_notification(app.clone(), title, Some(body)).await;
+                    let _ = send_native_notification(app.clone(), title, Some(body), None).await;
                 }
-
-                let _ = state.lock().unwrap().add_alerts(alerts);
             }
 
-            should_refresh = len > 0 || old_error.is_some();
-            state.lock().unwrap().alert_polling_error = None;
+            should_refresh = changed || old_error.is_some();
+            state
+                .lock()
+                .unwrap()
+                .server_state_mut(server)
+                .alert_polling_error = None;
+            Ok(())
         }
         Err(e) => {
-            tracing::error!("Failed to get alerts: {e}");
-            should_refresh =
-                old_error.is_none() || old_error != Some(POLLING_FAILED_MESSAGE.to_string());
-            state.lock().unwrap().alert_polling_error = Some(POLLING_FAILED_MESSAGE.to_string());
+            tracing::error!("Failed to get alerts of {server}: {e}");
+            should_refresh = old_error.as_ref() != Some(&e);
+            state
+                .lock()
+                .unwrap()
+                .server_state_mut(server)
+                .alert_polling_error = Some(e.clone());
+            Err(e)
         }
-    }
+    };
 
     if should_refresh {
         tracing::info!("Emitting alerts-refresh-page event");
         let _ = app.emit("alerts-refresh-page", ());
     }
+
+    result
+}
+
+/// Fetches the latest build of each watched configuration of the server, so that their current
+/// state is known even when none of their builds was notified since the app started. The checks
+/// stop at the first failure other than a configuration not found, which is returned.
+async fn fetch_configuration_statuses(
+    client: &QuickBuildClient,
+    app: &AppHandle<Wry>,
+    state: &Mutex<AppState>,
+) -> Result<(), QuickBuildError> {
+    let server = client.server();
+    let (watch, known_builds, old_statuses) = {
+        let state = state.lock().unwrap();
+        let known_builds: Vec<Build> = state
+            .get_builds()
+            .into_iter()
+            .filter(|build| build.server == server)
+            .collect();
+        let old_statuses: BTreeMap<i64, ConfigurationStatus> = state
+            .configuration_statuses
+            .iter()
+            .filter(|((status_server, _), _)| status_server == server)
+            .map(|((_, id), status)| (*id, status.clone()))
+            .collect();
+        (
+            WatchMatcher::from_settings(&state.settings),
+            known_builds,
+            old_statuses,
+        )
+    };
+    if watch.is_empty() && old_statuses.is_empty() {
+        return Ok(());
+    }
+
+    let mut failure = None;
+    let mut ids = watch.known_ids(server, &known_builds);
+    ids.extend(
+        old_statuses
+            .values()
+            .filter(|status| {
+                watch.is_watched_configuration(
+                    server,
+                    status.configuration,
+                    &status.configuration_path,
+                )
+            })
+            .map(|status| status.configuration),
+    );
+    for path in watch.literal_paths() {
+        if old_statuses
+            .values()
+            .any(|status| &status.configuration_path == path)
+        {
+            continue;
+        }
+        match client.get_configuration_id(path).await {
+            Ok(id) => {
+                ids.insert(id);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to resolve watched configuration {path} of {server}: {e}");
+                if !matches!(e, QuickBuildError::NotFound(_)) {
+                    failure = Some(e);
+                    break;
+                }
+            }
+        }
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let mut statuses = BTreeMap::new();
+    for id in ids {
+        let old_status = old_statuses.get(&id);
+        let latest_build = match &failure {
+            // the remaining configurations are not checked once the server failed
+            Some(e) => Err(e.clone()),
+            None => client.get_latest_build(id).await.inspect_err(|e| {
+                tracing::warn!("Failed to get latest build of configuration {id} of {server}: {e}")
+            }),
+        };
+        let status = match latest_build {
+            Ok(latest_build) => {
+                let configuration_path = match (&latest_build, old_status) {
+                    (Some(build), _) => build.configuration_path.clone(),
+                    (None, Some(old_status)) => old_status.configuration_path.clone(),
+                    (None, None) => client.get_configuration_path(id).await.unwrap_or_default(),
+                };
+                ConfigurationStatus {
+                    server: server.to_string(),
+                    configuration: id,
+                    configuration_path,
+                    latest_build,
+                    checked_time: now,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                if failure.is_none() && !matches!(e, QuickBuildError::NotFound(_)) {
+                    failure = Some(e.clone());
+                }
+                ConfigurationStatus {
+                    server: server.to_string(),
+                    configuration: id,
+                    configuration_path: old_status
+                        .map(|status| status.configuration_path.clone())
+                        .unwrap_or_default(),
+                    latest_build: old_status.and_then(|status| status.latest_build.clone()),
+                    checked_time: now,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        statuses.insert(id, status);
+    }
+
+    let changed = summarize_statuses(&statuses) != summarize_statuses(&old_statuses);
+    {
+        let mut state = state.lock().unwrap();
+        state
+            .configuration_statuses
+            .retain(|(status_server, _), _| status_server != server);
+        state.configuration_statuses.extend(
+            statuses
+                .into_iter()
+                .map(|(id, status)| ((server.to_string(), id), status)),
+        );
+    }
+    if changed {
+        tracing::info!("Emitting configuration-statuses-changed event");
+        let _ = app.emit("configuration-statuses-changed", ());
+    }
+    failure.map_or(Ok(()), Err)
+}
+
+/// What tells whether the statuses of the configurations changed between two checks
+fn summarize_statuses(
+    statuses: &BTreeMap<i64, ConfigurationStatus>,
+) -> Vec<(i64, Option<(i64, BuildStatus)>, Option<&str>)> {
+    statuses
+        .values()
+        .map(|status| {
+            (
+                status.configuration,
+                status
+                    .latest_build
+                    .as_ref()
+                    .map(|build| (build.id, build.status.clone())),
+                status.error.as_deref(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time::timeout;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wake_up_sent_while_polling_is_kept() {
+        let signal = PollSignal::new();
+        let mut receiver = signal.subscribe();
+
+        // woken up right away, as the wake up came before waiting
+        signal.wake_up();
+        let waited = timeout(
+            Duration::from_secs(5),
+            sleep_or_wake_up(Duration::from_secs(60), &mut receiver),
+        )
+        .await;
+        assert!(waited.is_ok());
+
+        // the wake up was consumed
+        let waited = timeout(
+            Duration::from_millis(50),
+            sleep_or_wake_up(Duration::from_secs(60), &mut receiver),
+        )
+        .await;
+        assert!(waited.is_err());
+    }
 }
@@ -1,113 +1,298 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error as _,
+    sync::Mutex,
+    time::Duration,
+};
 
 use bon::bon;
-use reqwest::{Client, StatusCode};
-use serde::de::DeserializeOwned;
+use reqwest::{Client, Method, StatusCode};
+use serde::{Serialize, de::DeserializeOwned};
+use specta::Type;
+use thiserror::Error;
+use tokio::sync::OnceCell;
 
 use crate::{
     constants::TRAY_MONITOR_NOTIFICATION_TYPE,
-    types::{alert::Alert, build::Build},
+    types::{
+        alert::Alert,
+        build::Build,
+        server::{ConnectionCheck, ConnectionDiagnostic, ServerProfile},
+    },
 };
 
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Coarse classification of [`QuickBuildError`], exposed to the frontend so it can
+/// tell users what to fix without parsing error messages.
+#[derive(Serialize, Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickBuildErrorKind {
+    Connect,
+    Tls,
+    Auth,
+    NotFound,
+    Server,
+    Timeout,
+    Decode,
+    Other,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum QuickBuildError {
+    #[error(
+        "Unable to connect to QuickBuild server, please check the server URL and your network: {0}"
+    )]
+    Connect(String),
+    #[error("Secure connection to QuickBuild server failed: {0}")]
+    Tls(String),
+    #[error("Authentication failed, your access token may have expired or is invalid")]
+    Unauthorized,
+    #[error("Access denied, the user is not allowed to access {0}")]
+    Forbidden(String),
+    #[error("Resource {0} not found, please check the server URL or upgrade QuickBuild")]
+    NotFound(String),
+    #[error("QuickBuild server is unavailable (status {0}), please try again later")]
+    Server(u16),
+    #[error("Request to QuickBuild server timed out")]
+    Timeout,
+    #[error("Failed to decode response from QuickBuild server: {0}")]
+    Decode(String),
+    #[error("Unexpected response from QuickBuild server (status {0})")]
+    Status(u16),
+    #[error("Request to QuickBuild server failed: {0}")]
+    Request(String),
+}
+
+impl QuickBuildError {
+    pub fn kind(&self) -> QuickBuildErrorKind {
+        match self {
+            Self::Connect(_) => QuickBuildErrorKind::Connect,
+            Self::Tls(_) => QuickBuildErrorKind::Tls,
+            Self::Unauthorized | Self::Forbidden(_) => QuickBuildErrorKind::Auth,
+            Self::NotFound(_) => QuickBuildErrorKind::NotFound,
+            Self::Server(_) => QuickBuildErrorKind::Server,
+            Self::Timeout => QuickBuildErrorKind::Timeout,
+            Self::Decode(_) => QuickBuildErrorKind::Decode,
+            Self::Status(_) | Self::Request(_) => QuickBuildErrorKind::Other,
+        }
+    }
+
+    /// Whether the request never got an answer from the server
+    pub fn is_unreachable(&self) -> bool {
+        matches!(
+            self,
+            Self::Connect(_) | Self::Tls(_) | Self::Timeout | Self::Request(_)
+        )
+    }
+
+    fn from_status(status: StatusCode, url: &str) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => Self::Unauthorized,
+            StatusCode::FORBIDDEN => Self::Forbidden(url.to_string()),
+            StatusCode::NOT_FOUND => Self::NotFound(url.to_string()),
+            s if s.is_server_error() => Self::Server(s.as_u16()),
+            s => Self::Status(s.as_u16()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for QuickBuildError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            return Self::Timeout;
+        }
+
+        if let Some(status) = e.status() {
+            let url = e.url().map(|u| u.path().to_string()).unwrap_or_default();
+            return Self::from_status(status, &url);
+        }
+
+        if e.is_connect() {
+            // reqwest reports TLS handshake failures as connect errors, so look into the
+            // source chain before falling back to a plain connect error
+            let mut source = e.source();
+            while let Some(cause) = source {
+                let message = cause.to_string();
+                let lower = message.to_lowercase();
+                if lower.contains("certificate")
+                    || lower.contains("tls")
+                    || lower.contains("handshake")
+                {
+                    return Self::Tls(message);
+                }
+                source = cause.source();
+            }
+            Self::Connect(e.to_string())
+        } else if e.is_decode() {
+            Self::Decode(e.to_string())
+        } else {
+            Self::Request(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for QuickBuildError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Decode(e.to_string())
+    }
+}
+
 pub struct QuickBuildClient {
+    /// Name of the server profile, the fetched builds and alerts are tagged with it
+    server: String,
     user: String,
     token: String,
     host: String,
     client: Client,
+    /// Id of the user, resolved from the user name on first use
+    user_id: OnceCell<i64>,
+    /// Paths of the configurations by id, kept as long as the client to save a request per
+    /// build of each poll
+    configuration_paths: Mutex<HashMap<i64, String>>,
+    /// Display names of the users by id, kept as long as the client for the same reason
+    display_names: Mutex<HashMap<i64, String>>,
 }
 
 #[bon]
 impl QuickBuildClient {
     #[builder]
-    pub fn new(user: String, token: String, host: String) -> Self {
+    pub fn new(server: String, user: String, token: String, host: String) -> Self {
         Self {
+            server,
             user,
             token,
             host,
-            client: Client::new(),
+            client: Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            user_id: OnceCell::new(),
+            configuration_paths: Mutex::default(),
+            display_names: Mutex::default(),
         }
     }
 
+    pub fn from_profile(profile: &ServerProfile) -> Self {
+        Self::builder()
+            .server(profile.name.clone())
+            .host(profile.server_url.clone())
+            .user(profile.user.clone())
+            .token(profile.token.clone())
+            .build()
+    }
+
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// Id of the user of the client, the builds requested by the user have it as requester
+    pub async fn get_user_id(&self) -> Result<i64, QuickBuildError> {
+        self.user_id
+            .get_or_try_init(|| self.get("rest/ids", vec![("user_name", self.user.clone())]))
+            .await
+            .copied()
+    }
+
+    /// Id of the user when it was already resolved
+    pub fn user_id(&self) -> Option<i64> {
+        self.user_id.get().copied()
+    }
+
     /// Generic GET method that deserializes JSON response to any type that implements Deserialize
     /// Supports both complex types (structs, Vec, etc.) and primitive types (String, i64, f64, etc.)
     async fn get<T: DeserializeOwned>(
         &self,
         url: &str,
         query: Vec<(&str, String)>,
-    ) -> Result<T, String> {
+    ) -> Result<T, QuickBuildError> {
         let text = self.get_raw(url, query).await?;
-        let result = serde_json::from_str::<T>(&text)
-            .map_err(|e| format!("Failed to deserialize response: {e}"))?;
+        let result = serde_json::from_str::<T>(&text)?;
         Ok(result)
     }
 
     /// Get raw response as String (for cases where you need the raw text)
-    async fn get_raw(&self, url: &str, query: Vec<(&str, String)>) -> Result<String, String> {
+    async fn get_raw(
+        &self,
+        url: &str,
+        query: Vec<(&str, String)>,
+    ) -> Result<String, QuickBuildError> {
+        self.send_raw(Method::GET, url, query).await
+    }
+
+    /// POST without body, for the operations changing state on the server
+    async fn post_raw(
+        &self,
+        url: &str,
+        query: Vec<(&str, String)>,
+    ) -> Result<String, QuickBuildError> {
+        self.send_raw(Method::POST, url, query).await
+    }
+
+    async fn send_raw(
+        &self,
+        method: Method,
+        url: &str,
+        query: Vec<(&str, String)>,
+    ) -> Result<String, QuickBuildError> {
         let full_url = format!("{}/{}", self.host, url);
-        tracing::debug!("Getting raw data from {}, query: {:?}", full_url, query);
+        tracing::debug!("Sending {} to {}, query: {:?}", method, full_url, query);
 
         let mut builder = self
             .client
-            .get(&full_url)
+            .request(method, &full_url)
             .basic_auth(self.user.as_str(), Some(self.token.as_str()))
             .header("Accept", "application/json");
         builder = builder.query(&query);
 
-        let response = builder.send().await.map_err(|e| e.to_string())?;
-        match response.error_for_status() {
-            Ok(r) => {
-                let text = r.text().await.map_err(|e| e.to_string())?;
-                Ok(text)
-            }
-            Err(e) => Err(format!(
-                "Failed to get data: {e}, status: {}",
-                e.status().unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
-            )),
+        let response = builder.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(QuickBuildError::from_status(status, url));
         }
+
+        Ok(response.text().await?)
     }
 
     pub async fn get_builds(
         &self,
         last_notified_build_id: Option<i64>,
-    ) -> Result<Vec<Build>, String> {
+    ) -> Result<Vec<Build>, QuickBuildError> {
         let mut queries = Vec::<(&str, String)>::new();
         if let Some(last_notified_build_id) = last_notified_build_id {
             queries.push(("last_notified_build_id", last_notified_build_id.to_string()));
         }
         let mut builds: Vec<Build> = self.get("rest/notifications", queries).await?;
-        let mut configuration_path_map = HashMap::<i64, String>::new(); // configuration id -> configuration path
-        let mut requester_name_map = HashMap::<i64, String>::new(); // requester id -> requester name
-
-        // update configuration path and requester name
-        for build in &mut builds {
-            let path: String = if let std::collections::hash_map::Entry::Vacant(e) =
-                configuration_path_map.entry(build.configuration)
-            {
-                let path = self.get_configuration_path(build.configuration).await?;
-                e.insert(path.clone());
-                path.clone()
-            } else {
-                configuration_path_map
-                    .get(&build.configuration)
-                    .unwrap()
-                    .clone()
-            };
-            build.configuration_path = path;
-            let requester_name = if let std::collections::hash_map::Entry::Vacant(e) =
-                requester_name_map.entry(build.requester)
-            {
-                let name = self.get_user_diplay_name(build.requester).await?;
-                e.insert(name.clone());
-                name.clone()
-            } else {
-                requester_name_map.get(&build.requester).unwrap().clone()
-            };
-            build.requester_name = Some(requester_name);
-        }
+        self.resolve_names(&mut builds).await?;
         Ok(builds)
     }
 
-    pub async fn get_alerts(&self, last_notified_time: Option<i64>) -> Result<Vec<Alert>, String> {
+    /// Latest build of the configuration, `None` when the configuration was never built
+    pub async fn get_latest_build(
+        &self,
+        configuration_id: i64,
+    ) -> Result<Option<Build>, QuickBuildError> {
+        let query = vec![
+            ("configuration_id", configuration_id.to_string()),
+            ("recursive", "false".to_string()),
+            ("count", "1".to_string()),
+        ];
+        let mut builds: Vec<Build> = self.get("rest/builds", query).await?;
+        builds.truncate(1);
+        self.resolve_names(&mut builds).await?;
+        Ok(builds.pop())
+    }
+
+    /// Id of the configuration with the given path, e.g. `root/product/release`
+    pub async fn get_configuration_id(&self, path: &str) -> Result<i64, QuickBuildError> {
+        self.get("rest/ids", vec![("configuration_path", path.to_string())])
+            .await
+    }
+
+    pub async fn get_alerts(
+        &self,
+        last_notified_time: Option<i64>,
+    ) -> Result<Vec<Alert>, QuickBuildError> {
         let mut queries = Vec::<(&str, String)>::new();
         queries.push(("notifier_type", TRAY_MONITOR_NOTIFICATION_TYPE.to_string()));
         if let Some(last_notified_time) = last_notified_time
@@ -116,17 +301,303 @@ impl QuickBuildClient {
             queries.push(("last_notified_time", last_notified_time.to_string()));
         }
 
-        let alerts: Vec<Alert> = self.get("rest/notifications/alerts", queries).await?;
+        let mut alerts: Vec<Alert> = self.get("rest/notifications/alerts", queries).await?;
+        for alert in &mut alerts {
+            alert.server = self.server.clone();
+        }
         Ok(alerts)
     }
 
-    async fn get_configuration_path(&self, id: i64) -> Result<String, String> {
-        self.get_raw(&format!("rest/configurations/{id}/path"), vec![])
+    /// Requests a new build of the configuration, with the given values of its variables,
+    /// returns the id of the build request
+    pub async fn request_build(
+        &self,
+        configuration_id: i64,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<String, QuickBuildError> {
+        tracing::info!("Requesting build of configuration {configuration_id}");
+        let mut query = vec![("configuration_id", configuration_id.to_string())];
+        if !variables.is_empty() {
+            query.push(("variables", format_variables(variables)));
+        }
+        self.post_raw("rest/trigger", query).await
+    }
+
+    /// Requests a new build of the configuration of the given build, with the variable values
+    /// the build was requested with, returns the id of the build request
+    pub async fn rebuild(
+        &self,
+        build_id: i64,
+        configuration_id: i64,
+    ) -> Result<String, QuickBuildError> {
+        let variables = self.get_build_variables(build_id).await?;
+        tracing::info!("Rebuilding build {build_id} of configuration {configuration_id}");
+        self.request_build(configuration_id, &variables).await
+    }
+
+    /// Values of the variables of the build, keyed by variable name
+    pub async fn get_build_variables(
+        &self,
+        build_id: i64,
+    ) -> Result<BTreeMap<String, String>, QuickBuildError> {
+        self.get(&format!("rest/builds/{build_id}/variable_values"), vec![])
+            .await
+    }
+
+    /// Stops a running build, or removes it from the queue when it is still waiting
+    pub async fn cancel_build(&self, build_id: i64) -> Result<(), QuickBuildError> {
+        tracing::info!("Cancelling build {build_id}");
+        self.post_raw("rest/stop", vec![("build_id", build_id.to_string())])
             .await
+            .map(|_| ())
     }
 
-    async fn get_user_diplay_name(&self, id: i64) -> Result<String, String> {
-        self.get_raw(&format!("rest/users/{id}/display_name"), vec![])
+    /// Version of the QuickBuild server, e.g. `14.0.12`
+    pub async fn get_version(&self) -> Result<String, QuickBuildError> {
+        self.get_raw("rest/version", vec![])
             .await
+            .map(|version| version.trim().to_string())
+    }
+
+    /// Checks the server URL, the credentials and the APIs polled by the tray monitor
+    pub async fn test_connection(&self) -> ConnectionDiagnostic {
+        tracing::info!("Testing connection to {}", self.host);
+        let version = self.get_version().await;
+        let notifications = match &version {
+            Err(e) if e.is_unreachable() || e.kind() == QuickBuildErrorKind::Auth => None,
+            _ => Some(
+                self.get::<Vec<Build>>("rest/notifications", vec![])
+                    .await
+                    .map(|_| ()),
+            ),
+        };
+        diagnose(version, notifications)
+    }
+
+    pub async fn acknowledge_alert(&self, alert_id: i64) -> Result<(), QuickBuildError> {
+        tracing::info!("Acknowledging alert {alert_id}");
+        self.post_raw(
+            &format!("rest/notifications/alerts/{alert_id}/acknowledge"),
+            vec![],
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Fills in the server, the configuration path and the requester name of the builds
+    async fn resolve_names(&self, builds: &mut [Build]) -> Result<(), QuickBuildError> {
+        for build in builds {
+            build.server = self.server.clone();
+            build.configuration_path = self.get_configuration_path(build.configuration).await?;
+            build.requester_name = Some(self.get_user_diplay_name(build.requester).await?);
+        }
+        Ok(())
+    }
+
+    pub async fn get_configuration_path(&self, id: i64) -> Result<String, QuickBuildError> {
+        let cached = self.configuration_paths.lock().unwrap().get(&id).cloned();
+        if let Some(path) = cached {
+            return Ok(path);
+        }
+        let path: String = self
+            .get_raw(&format!("rest/configurations/{id}/path"), vec![])
+            .await?;
+        self.configuration_paths
+            .lock()
+            .unwrap()
+            .insert(id, path.clone());
+        Ok(path)
+    }
+
+    async fn get_user_diplay_name(&self, id: i64) -> Result<String, QuickBuildError> {
+        let cached = self.display_names.lock().unwrap().get(&id).cloned();
+        if let Some(name) = cached {
+            return Ok(name);
+        }
+        let name: String = self
+            .get_raw(&format!("rest/users/{id}/display_name"), vec![])
+            .await?;
+        self.display_names.lock().unwrap().insert(id, name.clone());
+        Ok(name)
+    }
+}
+
+/// Tells the checks of the connection test from the responses of the version and the
+/// notifications APIs. The notifications API is not called when the server is unreachable or
+/// rejected the credentials.
+fn diagnose(
+    version: Result<String, QuickBuildError>,
+    notifications: Option<Result<(), QuickBuildError>>,
+) -> ConnectionDiagnostic {
+    let (reachable, mut authenticated, version_check, server_version) = match version {
+        Ok(version) => (
+            ConnectionCheck::passed(),
+            ConnectionCheck::passed(),
+            ConnectionCheck::passed(),
+            Some(version),
+        ),
+        Err(e) if e.is_unreachable() => (
+            ConnectionCheck::failed(&e),
+            ConnectionCheck::skipped(),
+            ConnectionCheck::skipped(),
+            None,
+        ),
+        Err(e) if e.kind() == QuickBuildErrorKind::Auth => (
+            ConnectionCheck::passed(),
+            ConnectionCheck::failed(&e),
+            ConnectionCheck::skipped(),
+            None,
+        ),
+        // older servers may not tell their version, the notifications API decides then
+        Err(e) => (
+            ConnectionCheck::passed(),
+            ConnectionCheck::skipped(),
+            ConnectionCheck::failed(&e),
+            None,
+        ),
+    };
+
+    let notifications_endpoint = match notifications {
+        None => ConnectionCheck::skipped(),
+        Some(Ok(())) => {
+            authenticated = ConnectionCheck::passed();
+            ConnectionCheck::passed()
+        }
+        Some(Err(e)) if e.kind() == QuickBuildErrorKind::Auth => {
+            authenticated = ConnectionCheck::failed(&e);
+            ConnectionCheck::skipped()
+        }
+        Some(Err(e)) => ConnectionCheck::failed(&e),
+    };
+
+    let ok = [
+        &reachable,
+        &authenticated,
+        &notifications_endpoint,
+        &version_check,
+    ]
+    .iter()
+    .all(|check| check.is_passed());
+    ConnectionDiagnostic {
+        reachable,
+        authenticated,
+        notifications_endpoint,
+        version: version_check,
+        server_version,
+        ok,
+    }
+}
+
+/// Formats variables the way the trigger API expects them, e.g. `[branch:main],[clean:true]`
+fn format_variables(variables: &BTreeMap<String, String>) -> String {
+    variables
+        .iter()
+        .map(|(name, value)| format!("[{name}:{value}]"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::server::CheckStatus;
+
+    #[test]
+    fn test_error_from_status() {
+        let url = "rest/notifications";
+        assert_eq!(
+            QuickBuildError::from_status(StatusCode::UNAUTHORIZED, url),
+            QuickBuildError::Unauthorized
+        );
+        assert_eq!(
+            QuickBuildError::from_status(StatusCode::FORBIDDEN, url).kind(),
+            QuickBuildErrorKind::Auth
+        );
+        assert_eq!(
+            QuickBuildError::from_status(StatusCode::NOT_FOUND, url),
+            QuickBuildError::NotFound(url.to_string())
+        );
+        assert_eq!(
+            QuickBuildError::from_status(StatusCode::BAD_GATEWAY, url),
+            QuickBuildError::Server(502)
+        );
+        assert_eq!(
+            QuickBuildError::from_status(StatusCode::BAD_REQUEST, url).kind(),
+            QuickBuildErrorKind::Other
+        );
+    }
+
+    #[test]
+    fn test_connect_error_tells_cause() {
+        let e = QuickBuildError::Connect("connection refused".to_string());
+        assert!(e.to_string().ends_with(": connection refused"));
+    }
+
+    #[test]
+    fn test_error_from_json() {
+        let e = serde_json::from_str::<Vec<Build>>("<html>").unwrap_err();
+        assert_eq!(QuickBuildError::from(e).kind(), QuickBuildErrorKind::Decode);
+    }
+
+    #[test]
+    fn test_diagnose_connection() {
+        let diagnostic = diagnose(Ok("14.0.12".to_string()), Some(Ok(())));
+        assert!(diagnostic.ok);
+        assert_eq!(diagnostic.server_version.as_deref(), Some("14.0.12"));
+
+        let diagnostic = diagnose(
+            Err(QuickBuildError::Connect("connection refused".to_string())),
+            None,
+        );
+        assert!(!diagnostic.ok);
+        assert_eq!(
+            diagnostic.reachable.error_kind,
+            Some(QuickBuildErrorKind::Connect)
+        );
+        assert_eq!(diagnostic.authenticated.status, CheckStatus::Skipped);
+        assert_eq!(
+            diagnostic.notifications_endpoint.status,
+            CheckStatus::Skipped
+        );
+
+        let diagnostic = diagnose(Err(QuickBuildError::Unauthorized), None);
+        assert!(diagnostic.reachable.is_passed());
+        assert_eq!(diagnostic.authenticated.status, CheckStatus::Failed);
+        assert_eq!(
+            diagnostic.notifications_endpoint.status,
+            CheckStatus::Skipped
+        );
+    }
+
+    #[test]
+    fn test_diagnose_missing_apis() {
+        // servers without the notifications API are too old for the tray monitor
+        let not_found = QuickBuildError::NotFound("rest/notifications".to_string());
+        let diagnostic = diagnose(Ok("5.1.0".to_string()), Some(Err(not_found.clone())));
+        assert!(!diagnostic.ok);
+        assert!(diagnostic.authenticated.is_passed());
+        assert_eq!(
+            diagnostic.notifications_endpoint,
+            ConnectionCheck::failed(&not_found)
+        );
+
+        // the credentials are still checked when the version is unknown
+        let diagnostic = diagnose(
+            Err(QuickBuildError::NotFound("rest/version".to_string())),
+            Some(Ok(())),
+        );
+        assert!(!diagnostic.ok);
+        assert!(diagnostic.authenticated.is_passed());
+        assert!(diagnostic.notifications_endpoint.is_passed());
+        assert_eq!(diagnostic.version.status, CheckStatus::Failed);
+    }
+
+    #[test]
+    fn test_format_variables() {
+        let variables = BTreeMap::from([
+            ("clean".to_string(), "true".to_string()),
+            ("branch".to_string(), "main".to_string()),
+        ]);
+        assert_eq!(format_variables(&variables), "[branch:main],[clean:true]");
     }
 }
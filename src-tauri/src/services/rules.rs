@@ -0,0 +1,321 @@
+use regex::Regex;
+
+use crate::types::{
+    alert::Alert,
+    build::Build,
+    rule::{
+        AlertRuleFilter, BuildRuleFilter, NotificationRule, PathPattern, RuleAction, RuleFilter,
+    },
+    settings::AppSettings,
+};
+
+/// Notification rules of the settings, ready to be evaluated against fetched builds and alerts
+#[derive(Debug)]
+pub struct RuleEngine {
+    rules: Vec<CompiledRule>,
+    /// Id of the user polling the server, `None` when it could not be resolved
+    user_id: Option<i64>,
+}
+
+#[derive(Debug)]
+struct CompiledRule {
+    filter: RuleFilter,
+    path: Option<Regex>,
+    action: RuleAction,
+}
+
+impl RuleEngine {
+    pub fn new(rules: &[NotificationRule], user_id: Option<i64>) -> Self {
+        let rules = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| match compile_rule(rule) {
+                Ok(compiled) => Some(compiled),
+                Err(e) => {
+                    tracing::warn!("Skipping notification rule '{}': {e}", rule.name);
+                    None
+                }
+            })
+            .collect();
+        Self { rules, user_id }
+    }
+
+    /// Rules of the settings, for the builds of the server the given user is polling
+    pub fn from_settings(settings: &AppSettings, user_id: Option<i64>) -> Self {
+        Self::new(&settings.notification_rules, user_id)
+    }
+
+    /// Action of the first rule matching the build, builds matched by no rule are notified
+    pub fn build_action(&self, build: &Build) -> RuleAction {
+        self.rules
+            .iter()
+            .find(|rule| match &rule.filter {
+                RuleFilter::Build(filter) => self.matches_build(filter, rule.path.as_ref(), build),
+                RuleFilter::Alert(_) => false,
+            })
+            .map_or(RuleAction::Notify, |rule| rule.action)
+    }
+
+    /// Action of the first rule matching the alert, alerts matched by no rule are notified
+    pub fn alert_action(&self, alert: &Alert) -> RuleAction {
+        self.rules
+            .iter()
+            .find(|rule| match &rule.filter {
+                RuleFilter::Alert(filter) => matches_alert(filter, alert),
+                RuleFilter::Build(_) => false,
+            })
+            .map_or(RuleAction::Notify, |rule| rule.action)
+    }
+
+    /// Drops the builds ignored by the rules
+    pub fn retain_builds(&self, builds: Vec<Build>) -> Vec<Build> {
+        builds
+            .into_iter()
+            .filter(|build| self.build_action(build) != RuleAction::Ignore)
+            .collect()
+    }
+
+    /// Drops the alerts ignored by the rules
+    pub fn retain_alerts(&self, alerts: Vec<Alert>) -> Vec<Alert> {
+        alerts
+            .into_iter()
+            .filter(|alert| self.alert_action(alert) != RuleAction::Ignore)
+            .collect()
+    }
+
+    fn matches_build(&self, filter: &BuildRuleFilter, path: Option<&Regex>, build: &Build) -> bool {
+        if path.is_some_and(|path| !path.is_match(&build.configuration_path)) {
+            return false;
+        }
+
+        if !filter.statuses.is_empty() && !filter.statuses.contains(&build.status) {
+            return false;
+        }
+
+        let is_requester = |name: &str| {
+            build
+                .requester_name
+                .as_deref()
+                .is_some_and(|requester| requester.eq_ignore_ascii_case(name.trim()))
+        };
+
+        if filter
+            .requester
+            .as_deref()
+            .is_some_and(|requester| !is_requester(requester))
+        {
+            return false;
+        }
+
+        !filter.only_mine || self.user_id == Some(build.requester)
+    }
+}
+
+fn matches_alert(filter: &AlertRuleFilter, alert: &Alert) -> bool {
+    (filter.priorities.is_empty() || filter.priorities.contains(&alert.priority))
+        && (filter.categories.is_empty() || filter.categories.contains(&alert.category))
+}
+
+/// Checks the rule before it is saved, so that it is not skipped when builds are notified
+pub fn validate_rule(rule: &NotificationRule) -> Result<(), String> {
+    compile_rule(rule)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid notification rule '{}': {e}", rule.name))
+}
+
+fn compile_rule(rule: &NotificationRule) -> Result<CompiledRule, String> {
+    let path = match &rule.filter {
+        RuleFilter::Build(BuildRuleFilter {
+            configuration_path: Some(pattern),
+            ..
+        }) => Some(compile_path_pattern(pattern)?),
+        _ => None,
+    };
+
+    Ok(CompiledRule {
+        filter: rule.filter.clone(),
+        path,
+        action: rule.action,
+    })
+}
+
+pub fn compile_path_pattern(pattern: &PathPattern) -> Result<Regex, String> {
+    let regex = match pattern {
+        PathPattern::Glob(glob) => glob_to_regex(glob),
+        PathPattern::Regex(regex) => regex.clone(),
+    };
+    Regex::new(&regex).map_err(|e| format!("Invalid configuration path pattern: {e}"))
+}
+
+/// Translates a configuration path glob into an anchored regex
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{alert::AlertPriority, build::BuildStatus};
+
+    fn rule(filter: RuleFilter, action: RuleAction) -> NotificationRule {
+        NotificationRule {
+            name: "test".to_string(),
+            enabled: true,
+            filter,
+            action,
+        }
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        let regex = Regex::new(&glob_to_regex("root/*/release")).unwrap();
+        assert!(regex.is_match("root/app/release"));
+        assert!(!regex.is_match("root/app/sub/release"));
+
+        let regex = Regex::new(&glob_to_regex("root/**")).unwrap();
+        assert!(regex.is_match("root/app/sub/release"));
+        assert!(!regex.is_match("other/app"));
+
+        let regex = Regex::new(&glob_to_regex("root/app.?")).unwrap();
+        assert!(regex.is_match("root/app.1"));
+        assert!(!regex.is_match("root/appx1"));
+    }
+
+    #[test]
+    fn test_validate_rule() {
+        let valid = rule(
+            RuleFilter::Build(BuildRuleFilter {
+                configuration_path: Some(PathPattern::Glob("root/**".to_string())),
+                ..Default::default()
+            }),
+            RuleAction::Ignore,
+        );
+        assert!(validate_rule(&valid).is_ok());
+
+        let invalid = rule(
+            RuleFilter::Build(BuildRuleFilter {
+                configuration_path: Some(PathPattern::Regex("[".to_string())),
+                ..Default::default()
+            }),
+            RuleAction::Ignore,
+        );
+        assert!(validate_rule(&invalid).unwrap_err().contains("'test'"));
+    }
+
+    #[test]
+    fn test_first_matching_rule_applies() {
+        let rules = vec![
+            rule(
+                RuleFilter::Build(BuildRuleFilter {
+                    configuration_path: Some(PathPattern::Glob("root/sandbox/**".to_string())),
+                    ..Default::default()
+                }),
+                RuleAction::Ignore,
+            ),
+            rule(
+                RuleFilter::Build(BuildRuleFilter {
+                    statuses: vec![BuildStatus::Successful],
+                    ..Default::default()
+                }),
+                RuleAction::Silent,
+            ),
+        ];
+        let engine = RuleEngine::new(&rules, Some(1));
+
+        let sandbox = Build::test()
+            .configuration_path("root/sandbox/app")
+            .status(BuildStatus::Failed)
+            .requester_name("admin")
+            .build();
+        assert_eq!(engine.build_action(&sandbox), RuleAction::Ignore);
+        let successful = Build::test()
+            .status(BuildStatus::Successful)
+            .requester_name("admin")
+            .build();
+        assert_eq!(engine.build_action(&successful), RuleAction::Silent);
+        let failed = Build::test()
+            .status(BuildStatus::Failed)
+            .requester_name("admin")
+            .build();
+        assert_eq!(engine.build_action(&failed), RuleAction::Notify);
+
+        let retained = engine.retain_builds(vec![sandbox, successful, failed]);
+        assert_eq!(retained.len(), 2);
+    }
+
+    #[test]
+    fn test_only_mine_and_disabled_rules() {
+        let mut rules = vec![
+            rule(
+                RuleFilter::Build(BuildRuleFilter {
+                    configuration_path: Some(PathPattern::Regex("[".to_string())),
+                    ..Default::default()
+                }),
+                RuleAction::Ignore,
+            ),
+            rule(
+                RuleFilter::Build(BuildRuleFilter {
+                    only_mine: true,
+                    ..Default::default()
+                }),
+                RuleAction::Notify,
+            ),
+            rule(
+                RuleFilter::Build(BuildRuleFilter::default()),
+                RuleAction::Silent,
+            ),
+        ];
+        let engine = RuleEngine::new(&rules, Some(1));
+        let mine = Build::test()
+            .status(BuildStatus::Failed)
+            .requester_name("admin")
+            .build();
+        let mut others = Build::test()
+            .status(BuildStatus::Failed)
+            .requester_name("admin")
+            .build();
+        // builds of another user with the same display name are not mine
+        others.requester = 2;
+        assert_eq!(engine.build_action(&mine), RuleAction::Notify);
+        assert_eq!(engine.build_action(&others), RuleAction::Silent);
+
+        // no build is mine while the id of the user is unknown
+        let engine = RuleEngine::new(&rules, None);
+        assert_eq!(engine.build_action(&mine), RuleAction::Silent);
+
+        rules[1].enabled = false;
+        let engine = RuleEngine::new(&rules, Some(1));
+        assert_eq!(engine.build_action(&mine), RuleAction::Silent);
+    }
+
+    #[test]
+    fn test_alert_rules() {
+        let rules = vec![rule(
+            RuleFilter::Alert(AlertRuleFilter {
+                priorities: vec![AlertPriority::Low],
+                categories: vec![],
+            }),
+            RuleAction::Silent,
+        )];
+        let engine = RuleEngine::new(&rules, Some(1));
+        let mut alert = Alert::test().priority(AlertPriority::Low).build();
+        assert_eq!(engine.alert_action(&alert), RuleAction::Silent);
+
+        alert.priority = AlertPriority::High;
+        assert_eq!(engine.alert_action(&alert), RuleAction::Notify);
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::{BTreeSet, HashSet};
+
+use regex::Regex;
+
+use crate::{
+    services::rules::compile_path_pattern,
+    types::{
+        build::Build,
+        rule::PathPattern,
+        settings::AppSettings,
+        watch::{WatchList, WatchedConfiguration},
+    },
+};
+
+/// Watch list of the settings, ready to be matched against fetched builds
+#[derive(Debug, Default)]
+pub struct WatchMatcher {
+    /// Watched ids, along with their server when they are not watched on every server
+    ids: HashSet<(Option<String>, i64)>,
+    paths: Vec<Regex>,
+    literal_paths: Vec<String>,
+    notify_only_watched: bool,
+    status_only_watched: bool,
+}
+
+impl WatchMatcher {
+    pub fn new(watch_list: &WatchList) -> Self {
+        let mut ids = HashSet::new();
+        let mut paths = Vec::new();
+        let mut literal_paths = Vec::new();
+        for configuration in &watch_list.configurations {
+            match configuration {
+                WatchedConfiguration::Id { id, server } => {
+                    ids.insert((server.clone(), *id));
+                }
+                WatchedConfiguration::Path { path: pattern } => match compile_path_pattern(pattern)
+                {
+                    Ok(path) => {
+                        if let PathPattern::Glob(glob) = pattern
+                            && !glob.contains(['*', '?'])
+                        {
+                            literal_paths.push(glob.trim().to_string());
+                        }
+                        paths.push(path);
+                    }
+                    Err(e) => tracing::warn!("Skipping watched configuration {pattern:?}: {e}"),
+                },
+            }
+        }
+
+        Self {
+            ids,
+            paths,
+            literal_paths,
+            notify_only_watched: watch_list.notify_only_watched,
+            status_only_watched: watch_list.status_only_watched,
+        }
+    }
+
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self::new(&settings.watch_list)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty() && self.paths.is_empty()
+    }
+
+    pub fn is_watched(&self, build: &Build) -> bool {
+        self.is_watched_configuration(
+            &build.server,
+            build.configuration,
+            &build.configuration_path,
+        )
+    }
+
+    pub fn is_watched_configuration(&self, server: &str, id: i64, path: &str) -> bool {
+        self.ids.contains(&(None, id))
+            || self.ids.contains(&(Some(server.to_string()), id))
+            || self.paths.iter().any(|regex| regex.is_match(path))
+    }
+
+    /// Ids of the watched configurations of the server, along with the configurations of its
+    /// known builds matching the watched paths
+    pub fn known_ids(&self, server: &str, known_builds: &[Build]) -> BTreeSet<i64> {
+        let mut ids: BTreeSet<i64> = self
+            .ids
+            .iter()
+            .filter(|(id_server, _)| id_server.as_deref().is_none_or(|name| name == server))
+            .map(|(_, id)| *id)
+            .collect();
+        ids.extend(
+            known_builds
+                .iter()
+                .filter(|build| build.server == server && self.is_watched(build))
+                .map(|build| build.configuration),
+        );
+        ids
+    }
+
+    /// Watched paths without wildcards, which can be resolved into configuration ids on the
+    /// server even when no build of them is known yet
+    pub fn literal_paths(&self) -> &[String] {
+        &self.literal_paths
+    }
+
+    /// Whether the build may be notified, builds of other configurations are only recorded
+    /// when notifications are restricted to the watch list
+    pub fn should_notify(&self, build: &Build) -> bool {
+        !self.notify_only_watched || self.is_empty() || self.is_watched(build)
+    }
+
+    /// Whether the build counts for the tray status
+    pub fn affects_status(&self, build: &Build) -> bool {
+        !self.status_only_watched || self.is_empty() || self.is_watched(build)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watched_by_id_or_path() {
+        let matcher = WatchMatcher::new(&WatchList {
+            configurations: vec![
+                WatchedConfiguration::Id {
+                    id: 2,
+                    server: None,
+                },
+                WatchedConfiguration::Path {
+                    path: PathPattern::Glob("root/product/**".to_string()),
+                },
+                WatchedConfiguration::Path {
+                    path: PathPattern::Regex("[".to_string()),
+                },
+            ],
+            notify_only_watched: true,
+            status_only_watched: false,
+        });
+
+        let by_id = Build::test()
+            .server("ci")
+            .configuration(2)
+            .configuration_path("root/infra")
+            .build();
+        let by_path = Build::test()
+            .server("ci")
+            .configuration(3)
+            .configuration_path("root/product/release")
+            .build();
+        let others = Build::test()
+            .server("ci")
+            .configuration(4)
+            .configuration_path("root/infra/nightly")
+            .build();
+        assert!(matcher.is_watched(&by_id));
+        assert!(matcher.is_watched(&by_path));
+        assert!(!matcher.is_watched(&others));
+
+        assert!(matcher.should_notify(&by_path));
+        assert!(!matcher.should_notify(&others));
+        assert!(matcher.affects_status(&others));
+
+        let ids = matcher.known_ids("ci", &[by_path, others]);
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), vec![2, 3]);
+        assert!(matcher.literal_paths().is_empty());
+    }
+
+    #[test]
+    fn test_watched_id_of_server() {
+        let matcher = WatchMatcher::new(&WatchList {
+            configurations: vec![WatchedConfiguration::Id {
+                id: 2,
+                server: Some("ci".to_string()),
+            }],
+            ..Default::default()
+        });
+
+        assert!(
+            matcher.is_watched(
+                &Build::test()
+                    .server("ci")
+                    .configuration(2)
+                    .configuration_path("root/infra")
+                    .build()
+            )
+        );
+        assert!(!matcher.is_watched_configuration("staging", 2, "root/infra"));
+        assert_eq!(matcher.known_ids("ci", &[]).len(), 1);
+        assert!(matcher.known_ids("staging", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_watched_configuration_format() {
+        let configurations: Vec<WatchedConfiguration> = serde_json::from_str(
+            r#"[{"id": 1}, {"id": 2, "server": "ci"}, {"path": {"glob": "root/**"}}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            configurations,
+            vec![
+                WatchedConfiguration::Id {
+                    id: 1,
+                    server: None
+                },
+                WatchedConfiguration::Id {
+                    id: 2,
+                    server: Some("ci".to_string())
+                },
+                WatchedConfiguration::Path {
+                    path: PathPattern::Glob("root/**".to_string())
+                },
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&configurations[0]).unwrap(),
+            r#"{"id":1}"#
+        );
+    }
+
+    #[test]
+    fn test_literal_paths() {
+        let matcher = WatchMatcher::new(&WatchList {
+            configurations: vec![
+                WatchedConfiguration::Path {
+                    path: PathPattern::Glob(" root/product ".to_string()),
+                },
+                WatchedConfiguration::Path {
+                    path: PathPattern::Glob("root/infra/*".to_string()),
+                },
+                WatchedConfiguration::Path {
+                    path: PathPattern::Regex("root/other".to_string()),
+                },
+            ],
+            ..Default::default()
+        });
+        assert_eq!(matcher.literal_paths(), ["root/product".to_string()]);
+    }
+
+    #[test]
+    fn test_empty_watch_list_restricts_nothing() {
+        let matcher = WatchMatcher::new(&WatchList {
+            configurations: vec![WatchedConfiguration::Path {
+                path: PathPattern::Regex("[".to_string()),
+            }],
+            notify_only_watched: true,
+            status_only_watched: true,
+        });
+        assert!(matcher.is_empty());
+
+        let others = Build::test()
+            .server("ci")
+            .configuration(4)
+            .configuration_path("root/infra")
+            .build();
+        assert!(matcher.should_notify(&others));
+        assert!(matcher.affects_status(&others));
+    }
+}
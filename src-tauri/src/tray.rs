@@ -1,20 +1,36 @@
-use std::{str::FromStr, sync::Mutex};
+use std::{collections::HashSet, str::FromStr, sync::Mutex};
 
 use serde::Deserialize;
 use strum::{Display, EnumString};
 use tauri::{
-    AppHandle, Emitter, Manager,
+    AppHandle, Emitter, Manager, Url,
     image::Image,
-    menu::{Menu, MenuBuilder, MenuId, MenuItem, PredefinedMenuItem},
+    menu::{
+        CheckMenuItem, Menu, MenuBuilder, MenuId, MenuItem, PredefinedMenuItem, Submenu,
+        SubmenuBuilder,
+    },
     tray::TrayIconBuilder,
 };
+use time::{Duration, OffsetDateTime};
 
 use crate::{
     AppState,
-    commands::windows::{show_dashboard_window, show_main_window},
-    constants::TRAY_ID,
-    types::settings::AppSettings,
-    utils::platform::{is_macos, is_windows},
+    commands::{
+        alerts::acknowledge_all_alerts,
+        notifications::send_native_notification,
+        settings::set_paused,
+        windows::{show_build_in_dashboard, show_dashboard_window, show_main_window},
+    },
+    constants::{DATE_TIME_FORMAT, MAX_DIGEST_LINES, TRAY_ID, TRAY_RECENT_BUILDS},
+    services::{poll::poll_now, quickbuild::QuickBuildError, watch::WatchMatcher},
+    types::{
+        build::{Build, BuildStatus},
+        settings::AppSettings,
+    },
+    utils::{
+        platform::{is_macos, is_windows},
+        times::{format_date_time, odt_to_pdt, start_of_next_local_day, to_local},
+    },
 };
 
 const ICON_NO_CONFIG: &[u8] = include_bytes!("../icons/tray/tray-no-config.png");
@@ -46,13 +62,29 @@ pub enum TrayItem {
     ViewBuilds,
     ClearBuilds,
     ViewAlerts,
+    AcknowledgeAlerts,
     ClearAlerts,
+    RefreshNow,
     Paused,
+    PauseOneHour,
+    PauseUntilTomorrow,
     Preferences,
     About,
     Quit,
 }
 
+/// Menu id prefix of the items in the "Recent Builds" submenu, followed by the build id and
+/// the name of its server, as in `build_42@default`
+const RECENT_BUILD_ID_PREFIX: &str = "build_";
+
+/// Server name and build id of a "Recent Builds" item
+fn parse_recent_build_id(menu_id: &str) -> Option<(&str, i64)> {
+    let (build_id, server) = menu_id
+        .strip_prefix(RECENT_BUILD_ID_PREFIX)?
+        .split_once('@')?;
+    Some((server, build_id.parse().ok()?))
+}
+
 impl TryFrom<MenuId> for TrayItem {
     type Error = String;
 
@@ -62,7 +94,51 @@ impl TryFrom<MenuId> for TrayItem {
     }
 }
 
+fn format_local_time(date_time: OffsetDateTime) -> String {
+    let local = to_local(date_time);
+    format_date_time(odt_to_pdt(local), DATE_TIME_FORMAT).unwrap_or_else(|_| local.to_string())
+}
+
+fn get_pause_text(settings: &AppSettings) -> String {
+    let until = settings
+        .paused_until
+        .filter(|_| settings.paused)
+        .map(format_local_time);
+
+    match until {
+        Some(until) => format!("Pause Polling (until {until})"),
+        None => "Pause Polling".to_string(),
+    }
+}
+
+fn build_recent_builds_submenu(
+    app: &AppHandle,
+    builds: &[Build],
+) -> tauri::Result<Submenu<tauri::Wry>> {
+    let mut builder = SubmenuBuilder::new(app, "Recent Builds");
+    if builds.is_empty() {
+        builder = builder.item(&MenuItem::new(app, "No Builds", false, None::<&str>)?);
+    }
+
+    for build in builds {
+        builder = builder.item(&MenuItem::with_id(
+            app,
+            format!("{RECENT_BUILD_ID_PREFIX}{}@{}", build.id, build.server),
+            build.get_menu_text(),
+            true,
+            None::<&str>,
+        )?);
+    }
+
+    builder.build()
+}
+
 fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let settings = get_app_settings(app);
+    let recent_builds: Vec<Build> = get_app_builds(app)
+        .into_iter()
+        .take(TRAY_RECENT_BUILDS)
+        .collect();
     let menu = MenuBuilder::new(app)
         .item(&MenuItem::with_id(
             app,
@@ -79,6 +155,7 @@ fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
             true,
             None::<&str>,
         )?)
+        .item(&build_recent_builds_submenu(app, &recent_builds)?)
         .item(&MenuItem::with_id(
             app,
             TrayItem::ClearBuilds,
@@ -94,6 +171,13 @@ fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
             true,
             None::<&str>,
         )?)
+        .item(&MenuItem::with_id(
+            app,
+            TrayItem::AcknowledgeAlerts,
+            "Acknowledge All Alerts",
+            true,
+            None::<&str>,
+        )?)
         .item(&MenuItem::with_id(
             app,
             TrayItem::ClearAlerts,
@@ -102,6 +186,40 @@ fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
             None::<&str>,
         )?)
         .item(&PredefinedMenuItem::separator(app)?)
+        .item(&MenuItem::with_id(
+            app,
+            TrayItem::RefreshNow,
+            "Refresh Now",
+            !settings.paused,
+            None::<&str>,
+        )?)
+        .item(&CheckMenuItem::with_id(
+            app,
+            TrayItem::Paused,
+            get_pause_text(&settings),
+            true,
+            settings.paused,
+            None::<&str>,
+        )?)
+        .item(
+            &SubmenuBuilder::new(app, "Pause For")
+                .item(&MenuItem::with_id(
+                    app,
+                    TrayItem::PauseOneHour,
+                    "1 Hour",
+                    true,
+                    None::<&str>,
+                )?)
+                .item(&MenuItem::with_id(
+                    app,
+                    TrayItem::PauseUntilTomorrow,
+                    "Until Tomorrow",
+                    true,
+                    None::<&str>,
+                )?)
+                .build()?,
+        )
+        .item(&PredefinedMenuItem::separator(app)?)
         .item(&MenuItem::with_id(
             app,
             TrayItem::Preferences,
@@ -128,6 +246,22 @@ fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
     Ok(menu)
 }
 
+/// Rebuilds the tray menu so that it reflects the current settings
+pub fn refresh_tray_menu(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    match build_tray_menu(app) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                tracing::error!("Failed to set tray menu: {e}");
+            }
+        }
+        Err(e) => tracing::error!("Failed to build tray menu: {e}"),
+    }
+}
+
 fn initial_icon() -> tauri::Result<Image<'static>> {
     #[cfg(target_os = "macos")]
     {
@@ -146,7 +280,6 @@ fn initial_icon() -> tauri::Result<Image<'static>> {
     Err(tauri::Error::Other("Unsupported platform".to_string()))
 }
 
-#[allow(unused)]
 fn get_tray_status_icon(status: TrayStatus) -> tauri::Result<Image<'static>> {
     match status {
         TrayStatus::NoConfig => Ok(Image::from_bytes(ICON_NO_CONFIG)?),
@@ -157,7 +290,158 @@ fn get_tray_status_icon(status: TrayStatus) -> tauri::Result<Image<'static>> {
     }
 }
 
-#[allow(unused)]
+/// Computes the tray status from the settings, the builds in the store (newest first) and
+/// whether the last poll failed, only the watched configurations count when the watch list
+/// restricts the status
+pub fn get_tray_status(
+    settings: &AppSettings,
+    builds: &[Build],
+    polling_failed: bool,
+) -> TrayStatus {
+    if !settings.is_configured() {
+        return TrayStatus::NoConfig;
+    }
+
+    if settings.paused {
+        return TrayStatus::Paused;
+    }
+
+    if polling_failed {
+        return TrayStatus::Error;
+    }
+
+    // only the latest build of each configuration tells its current state
+    let watch = WatchMatcher::from_settings(settings);
+    let mut seen = HashSet::new();
+    let latest_builds: Vec<&Build> = builds
+        .iter()
+        .filter(|build| watch.affects_status(build))
+        .filter(|build| seen.insert((&build.server, build.configuration)))
+        .collect();
+
+    if latest_builds.iter().any(|build| build.status.is_failure()) {
+        TrayStatus::Error
+    } else if latest_builds
+        .iter()
+        .any(|build| matches!(build.status, BuildStatus::Running))
+    {
+        TrayStatus::Running
+    } else {
+        TrayStatus::Success
+    }
+}
+
+/// Builds the tray tooltip, a short summary of the monitoring state
+pub fn get_tray_tooltip(
+    settings: &AppSettings,
+    last_polling_time: Option<OffsetDateTime>,
+    failed_builds: usize,
+    unacknowledged_alerts: usize,
+    unread_builds: u32,
+    unread_alerts: u32,
+    polling_error: Option<&QuickBuildError>,
+) -> String {
+    if !settings.is_configured() {
+        return "QuickBuild Tray Monitor - not configured".to_string();
+    }
+
+    let servers = settings
+        .get_servers()
+        .iter()
+        .map(|server| {
+            Url::parse(&server.server_url)
+                .ok()
+                .and_then(|url| {
+                    url.host_str().map(|host| match url.port() {
+                        Some(port) => format!("{host}:{port}"),
+                        None => host.to_string(),
+                    })
+                })
+                .unwrap_or_else(|| server.server_url.clone())
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut lines = vec![format!("QuickBuild: {servers}")];
+    if settings.paused {
+        lines.push("Polling paused".to_string());
+    }
+    lines.push(match last_polling_time {
+        Some(time) => format!("Last polled: {}", format_local_time(time)),
+        None => "Not polled yet".to_string(),
+    });
+    lines.push(format!("Failed builds: {failed_builds}"));
+    lines.push(format!("Unacknowledged alerts: {unacknowledged_alerts}"));
+    if unread_builds > 0 || unread_alerts > 0 {
+        lines.push(format!(
+            "Unread: {unread_builds} builds, {unread_alerts} alerts"
+        ));
+    }
+    if let Some(e) = polling_error {
+        lines.push(format!("Error: {e}"));
+    }
+
+    lines.join("\n")
+}
+
+/// Updates the tray icon and tooltip to reflect the current app state
+pub fn refresh_tray_status(app: &AppHandle) {
+    let (status, tooltip) = {
+        let state = app.state::<Mutex<AppState>>();
+        let Ok(state_guard) = state.lock() else {
+            tracing::error!("Failed to acquire lock for refreshing tray status");
+            return;
+        };
+        let builds = state_guard.get_builds();
+        let polling_error = state_guard.get_polling_error();
+        // latest builds of the watched configurations tell their state even when they are
+        // missing in the history
+        let mut status_builds = builds.clone();
+        status_builds.extend(
+            state_guard
+                .configuration_statuses
+                .values()
+                .filter_map(|status| status.latest_build.clone()),
+        );
+        status_builds.sort_by(|a, b| b.id.cmp(&a.id));
+        let status = get_tray_status(
+            &state_guard.settings,
+            &status_builds,
+            polling_error.is_some(),
+        );
+
+        let watch = WatchMatcher::from_settings(&state_guard.settings);
+        let failed_builds = builds
+            .iter()
+            .filter(|build| build.status.is_failure() && !build.read && watch.affects_status(build))
+            .count();
+        let unacknowledged_alerts = state_guard
+            .get_alerts()
+            .iter()
+            .filter(|alert| !alert.fixed && !alert.is_acknowledged())
+            .count();
+        let tooltip = get_tray_tooltip(
+            &state_guard.settings,
+            state_guard.get_last_polling_time(),
+            failed_builds,
+            unacknowledged_alerts,
+            state_guard.get_unread_builds_count(),
+            state_guard.get_unread_alerts_count(),
+            polling_error,
+        );
+        (status, tooltip)
+    };
+
+    tracing::debug!("Updating tray status to {status}");
+    update_tray_icon(app, status);
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID)
+        && let Err(e) = tray.set_tooltip(Some(tooltip))
+    {
+        tracing::warn!("Failed to set tray tooltip: {e}");
+    }
+}
+
 pub fn update_tray_icon(app: &AppHandle, status: TrayStatus) {
     if is_windows() {
         return;
@@ -168,7 +452,9 @@ pub fn update_tray_icon(app: &AppHandle, status: TrayStatus) {
     };
 
     if let Ok(icon) = get_tray_status_icon(status) {
+        // status icons are colored, so they must not be rendered as template images on macOS
         let _ = tray.set_icon(Some(icon));
+        let _ = tray.set_icon_as_template(false);
     }
 }
 
@@ -180,6 +466,28 @@ fn get_app_settings(app: &AppHandle) -> AppSettings {
         .clone()
 }
 
+fn get_app_builds(app: &AppHandle) -> Vec<Build> {
+    app.state::<Mutex<AppState>>().lock().unwrap().get_builds()
+}
+
+/// Tells the user about the alerts failing to be acknowledged from the tray, which has no other
+/// place to show errors
+async fn notify_acknowledge_errors(app: AppHandle, errors: Vec<String>) {
+    let title = match errors.len() {
+        1 => "Failed to acknowledge 1 alert".to_string(),
+        len => format!("Failed to acknowledge {len} alerts"),
+    };
+    let body = errors
+        .iter()
+        .take(MAX_DIGEST_LINES)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Err(e) = send_native_notification(app, title, Some(body), None).await {
+        tracing::error!("Failed to notify the alerts failing to be acknowledged: {e}");
+    }
+}
+
 pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
     let menu = build_tray_menu(app)?;
     let app = app.clone();
@@ -191,6 +499,16 @@ pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
         .show_menu_on_left_click(true)
         .on_menu_event({
             move |app: &AppHandle, event| {
+                if let Some((server, build_id)) = parse_recent_build_id(&event.id.0) {
+                    tracing::debug!("Show build {build_id} of {server} event received");
+                    if let Err(e) =
+                        show_build_in_dashboard(app.clone(), server.to_string(), build_id)
+                    {
+                        tracing::error!("Failed to show build {build_id}: {e}");
+                    }
+                    return;
+                }
+
                 let item = TrayItem::try_from(event.id);
                 let settings = get_app_settings(app);
                 if item == Ok(TrayItem::Quit) {
@@ -218,6 +536,8 @@ pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
                         tracing::debug!("Clear builds event received");
                         let state = app.state::<Mutex<AppState>>();
                         let _ = state.lock().unwrap().clear_builds();
+                        refresh_tray_menu(app);
+                        refresh_tray_status(app);
                         let _ = app.emit("menu-view-builds", ());
                     }
                     Ok(TrayItem::ViewAlerts) => {
@@ -225,12 +545,62 @@ pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
                         let _ = app.emit("menu-view-alerts", ());
                         let _ = show_main_window(app.clone(), Some("Alerts"));
                     }
+                    Ok(TrayItem::AcknowledgeAlerts) => {
+                        tracing::debug!("Acknowledge alerts event received");
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            let errors = match acknowledge_all_alerts(app.clone()).await {
+                                Ok(result) => {
+                                    tracing::info!("{} alerts acknowledged", result.acknowledged);
+                                    result.errors
+                                }
+                                Err(e) => {
+                                    tracing::error!("Failed to acknowledge alerts: {e}");
+                                    vec![e]
+                                }
+                            };
+                            if !errors.is_empty() {
+                                notify_acknowledge_errors(app, errors).await;
+                            }
+                        });
+                    }
                     Ok(TrayItem::ClearAlerts) => {
                         tracing::debug!("Clear alerts event received");
                         let state = app.state::<Mutex<AppState>>();
                         let _ = state.lock().unwrap().clear_alerts();
+                        refresh_tray_status(app);
                         let _ = app.emit("menu-view-alerts", ());
                     }
+                    Ok(TrayItem::RefreshNow) => {
+                        tracing::debug!("Refresh now event received");
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            match poll_now(&app).await {
+                                Ok(servers) => tracing::info!("{} servers polled", servers.len()),
+                                Err(e) => tracing::error!("Failed to poll servers: {e}"),
+                            }
+                        });
+                    }
+                    Ok(TrayItem::Paused) => {
+                        tracing::debug!("Toggle pause event received");
+                        if let Err(e) = set_paused(app, !settings.paused, None) {
+                            tracing::error!("Failed to toggle pause: {e}");
+                        }
+                    }
+                    Ok(TrayItem::PauseOneHour) => {
+                        tracing::debug!("Pause for one hour event received");
+                        let until = OffsetDateTime::now_utc() + Duration::hours(1);
+                        if let Err(e) = set_paused(app, true, Some(until)) {
+                            tracing::error!("Failed to pause polling: {e}");
+                        }
+                    }
+                    Ok(TrayItem::PauseUntilTomorrow) => {
+                        tracing::debug!("Pause until tomorrow event received");
+                        let until = start_of_next_local_day(OffsetDateTime::now_utc());
+                        if let Err(e) = set_paused(app, true, Some(until)) {
+                            tracing::error!("Failed to pause polling: {e}");
+                        }
+                    }
                     Ok(TrayItem::Preferences) => {
                         tracing::debug!("Preferences event received");
                         let _ = app.emit("menu-view-settings", ());
@@ -263,15 +633,187 @@ pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::watch::{WatchList, WatchedConfiguration};
+
+    fn configured_settings() -> AppSettings {
+        AppSettings {
+            server_url: "http://localhost:8810".to_string(),
+            user: "admin".to_string(),
+            token: "token".to_string(),
+            ..AppSettings::default()
+        }
+    }
 
     #[test]
     fn test_tray_item_from_menu_id() {
         let menu_id = MenuId::from("dashboard");
         let tray_item = TrayItem::try_from(menu_id).unwrap();
         assert_eq!(tray_item, TrayItem::Dashboard);
+        assert_eq!(
+            TrayItem::try_from(MenuId::from("refresh_now")),
+            Ok(TrayItem::RefreshNow)
+        );
 
         let invalid_menu_id = MenuId::from("invalid_item");
         let result = TrayItem::try_from(invalid_menu_id);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_recent_build_id() {
+        assert_eq!(
+            parse_recent_build_id("build_42@default"),
+            Some(("default", 42))
+        );
+        assert_eq!(
+            parse_recent_build_id("build_42@ci@example"),
+            Some(("ci@example", 42))
+        );
+        assert_eq!(parse_recent_build_id("build_42"), None);
+        assert_eq!(parse_recent_build_id("dashboard"), None);
+    }
+
+    #[test]
+    fn test_tray_status_not_configured_or_paused() {
+        let builds = vec![
+            Build::test()
+                .id(1)
+                .configuration(1)
+                .status(BuildStatus::Failed)
+                .build(),
+        ];
+        assert_eq!(
+            get_tray_status(&AppSettings::default(), &builds, true),
+            TrayStatus::NoConfig
+        );
+
+        let settings = AppSettings {
+            paused: true,
+            ..configured_settings()
+        };
+        assert_eq!(
+            get_tray_status(&settings, &builds, true),
+            TrayStatus::Paused
+        );
+    }
+
+    #[test]
+    fn test_tray_status_from_latest_builds() {
+        let settings = configured_settings();
+        assert_eq!(get_tray_status(&settings, &[], false), TrayStatus::Success);
+        assert_eq!(get_tray_status(&settings, &[], true), TrayStatus::Error);
+
+        // configuration 1 was fixed by build 3, configuration 2 is still running
+        let builds = vec![
+            Build::test()
+                .id(4)
+                .configuration(2)
+                .status(BuildStatus::Running)
+                .build(),
+            Build::test()
+                .id(3)
+                .configuration(1)
+                .status(BuildStatus::Successful)
+                .build(),
+            Build::test()
+                .id(2)
+                .configuration(1)
+                .status(BuildStatus::Failed)
+                .build(),
+        ];
+        assert_eq!(
+            get_tray_status(&settings, &builds, false),
+            TrayStatus::Running
+        );
+
+        let builds = vec![
+            Build::test()
+                .id(4)
+                .configuration(2)
+                .status(BuildStatus::Timeout)
+                .build(),
+            Build::test()
+                .id(3)
+                .configuration(1)
+                .status(BuildStatus::Running)
+                .build(),
+        ];
+        assert_eq!(
+            get_tray_status(&settings, &builds, false),
+            TrayStatus::Error
+        );
+    }
+
+    #[test]
+    fn test_tray_status_from_watched_configurations() {
+        let mut settings = configured_settings();
+        settings.watch_list = WatchList {
+            configurations: vec![WatchedConfiguration::Id {
+                id: 1,
+                server: None,
+            }],
+            notify_only_watched: false,
+            status_only_watched: true,
+        };
+
+        // configuration 2 is not watched, so its failure does not turn the tray red
+        let builds = vec![
+            Build::test()
+                .id(4)
+                .configuration(2)
+                .status(BuildStatus::Failed)
+                .build(),
+            Build::test()
+                .id(3)
+                .configuration(1)
+                .status(BuildStatus::Successful)
+                .build(),
+        ];
+        assert_eq!(
+            get_tray_status(&settings, &builds, false),
+            TrayStatus::Success
+        );
+
+        settings.watch_list.status_only_watched = false;
+        assert_eq!(
+            get_tray_status(&settings, &builds, false),
+            TrayStatus::Error
+        );
+    }
+
+    #[test]
+    fn test_tray_tooltip() {
+        assert_eq!(
+            get_tray_tooltip(&AppSettings::default(), None, 0, 0, 0, 0, None),
+            "QuickBuild Tray Monitor - not configured"
+        );
+
+        let settings = configured_settings();
+        assert_eq!(
+            get_tray_tooltip(&settings, None, 2, 1, 0, 0, None),
+            "QuickBuild: localhost:8810\nNot polled yet\nFailed builds: 2\nUnacknowledged alerts: 1"
+        );
+        assert!(
+            get_tray_tooltip(&settings, None, 2, 1, 3, 1, None)
+                .ends_with("\nUnread: 3 builds, 1 alerts")
+        );
+
+        let settings = AppSettings {
+            paused: true,
+            ..configured_settings()
+        };
+        let tooltip = get_tray_tooltip(
+            &settings,
+            None,
+            0,
+            0,
+            0,
+            0,
+            Some(&QuickBuildError::Unauthorized),
+        );
+        assert!(tooltip.contains("\nPolling paused\n"));
+        assert!(tooltip.ends_with(
+            "\nError: Authentication failed, your access token may have expired or is invalid"
+        ));
+    }
 }
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use specta::Type;
 use strum::{Display, EnumString};
 
-#[derive(Serialize, Deserialize, Type, Debug, Clone, EnumString, Display)]
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq, Eq, EnumString, Display)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum AlertPriority {
     Low,
@@ -10,7 +10,7 @@ pub enum AlertPriority {
     High,
 }
 
-#[derive(Serialize, Deserialize, Type, Debug, Clone, EnumString, Display)]
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq, Eq, EnumString, Display)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum AlertCategory {
     System,
@@ -21,6 +21,9 @@ pub enum AlertCategory {
 #[serde(rename_all = "camelCase")]
 pub struct Alert {
     pub id: i64,
+    /// Name of the server the alert comes from, set once fetched
+    #[serde(skip_deserializing)]
+    pub server: String,
     pub subject: String,
     pub priority: AlertPriority,
     pub category: AlertCategory,
@@ -29,4 +32,46 @@ pub struct Alert {
     pub fixed: bool,
     pub ctime: i64,
     pub ack_time: i64,
+    /// Whether the alert was marked as read, only tracked in the local history
+    #[serde(default)]
+    pub read: bool,
+}
+
+impl Alert {
+    pub fn is_acknowledged(&self) -> bool {
+        self.ack_time > 0
+    }
+}
+
+#[cfg(test)]
+#[bon::bon]
+impl Alert {
+    /// Alert for the tests, the fields not given get plausible defaults
+    #[builder(finish_fn = build)]
+    pub fn test(
+        #[builder(default = 1)] id: i64,
+        #[builder(into, default = "default".to_string())] server: String,
+        // defaults to `Alert <id>`
+        #[builder(into)] subject: Option<String>,
+        #[builder(default = AlertPriority::High)] priority: AlertPriority,
+        #[builder(default = AlertCategory::System)] category: AlertCategory,
+        #[builder(default)] fixed: bool,
+        #[builder(default)] ctime: i64,
+        #[builder(default)] ack_time: i64,
+        #[builder(default)] read: bool,
+    ) -> Self {
+        Self {
+            id,
+            server,
+            subject: subject.unwrap_or_else(|| format!("Alert {id}")),
+            priority,
+            category,
+            alert_message: "Disk space is low".to_string(),
+            trigger: "disk".to_string(),
+            fixed,
+            ctime,
+            ack_time,
+            read,
+        }
+    }
 }
@@ -3,7 +3,9 @@ use specta::Type;
 use strum::{Display, EnumString};
 use time::OffsetDateTime;
 
-#[derive(Serialize, Deserialize, Type, Debug, Clone, EnumString, Display)]
+use crate::utils::times::format_duration;
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq, Eq, EnumString, Display)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum BuildStatus {
     Successful,
@@ -18,6 +20,9 @@ pub enum BuildStatus {
 #[serde(rename_all = "camelCase")]
 pub struct Build {
     pub id: i64,
+    /// Name of the server the build comes from, set once fetched
+    #[serde(skip_deserializing)]
+    pub server: String,
     pub configuration: i64,
     #[serde(default)]
     pub configuration_path: String,
@@ -41,6 +46,110 @@ pub struct Build {
     pub status_date: Option<OffsetDateTime>,
     pub duration: i64,
     pub wait_duration: i64,
+    /// Whether the build was marked as read, only tracked in the local history
+    #[serde(default)]
+    pub read: bool,
+}
+
+#[derive(Serialize, Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionKind {
+    /// First failure of a configuration after a success
+    Broke,
+    /// First success of a configuration after failures
+    Fixed,
+}
+
+/// Build changing the health of its configuration
+#[derive(Serialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildTransition {
+    pub kind: TransitionKind,
+    pub build: Build,
+    /// Number of failed builds in a row before a fix
+    pub failure_count: u32,
+    /// How long the configuration was failing before a fix, in millis
+    pub failing_duration: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+}
+
+fn default_query_limit() -> u32 {
+    50
+}
+
+/// Filters, sort order and page of a build history query, unset filters match every build
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildQuery {
+    /// Name of the server the builds come from
+    #[serde(default)]
+    pub server: Option<String>,
+    #[serde(default)]
+    pub statuses: Vec<BuildStatus>,
+    /// Configuration path prefix such as `root/product`, or a glob such as `root/*/release`
+    #[serde(default)]
+    pub configuration_path: Option<String>,
+    /// Part of the requester name, case insensitive
+    #[serde(default)]
+    pub requester: Option<String>,
+    /// Part of the build version, case insensitive
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default, with = "crate::serde::option_four_year_iso8601")]
+    pub begin_date_from: Option<OffsetDateTime>,
+    #[serde(default, with = "crate::serde::option_four_year_iso8601")]
+    pub begin_date_to: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub sort_order: SortOrder,
+    #[serde(default)]
+    pub offset: u32,
+    #[serde(default = "default_query_limit")]
+    pub limit: u32,
+}
+
+impl Default for BuildQuery {
+    fn default() -> Self {
+        Self {
+            server: None,
+            statuses: Vec::new(),
+            configuration_path: None,
+            requester: None,
+            version: None,
+            begin_date_from: None,
+            begin_date_to: None,
+            sort_order: SortOrder::default(),
+            offset: 0,
+            limit: default_query_limit(),
+        }
+    }
+}
+
+impl BuildStatus {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, BuildStatus::Failed | BuildStatus::Timeout)
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, BuildStatus::Successful | BuildStatus::Recommended)
+    }
+
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            BuildStatus::Successful => "✅",
+            BuildStatus::Recommended => "⭐",
+            BuildStatus::Failed => "❌",
+            BuildStatus::Cancelled => "⛔",
+            BuildStatus::Timeout => "⏰",
+            BuildStatus::Running => "🔄",
+        }
+    }
 }
 
 impl Build {
@@ -55,6 +164,20 @@ impl Build {
         }
     }
 
+    pub fn get_menu_text(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.status.glyph(),
+            self.configuration_path,
+            self.version
+        )
+    }
+
+    /// When the build reached its current status
+    pub fn get_status_date(&self) -> OffsetDateTime {
+        self.status_date.unwrap_or(self.begin_date)
+    }
+
     pub fn get_body(&self) -> Result<String, String> {
         Ok(format!(
             r#"
@@ -66,3 +189,72 @@ Triggered by:     {}
         ))
     }
 }
+
+#[cfg(test)]
+#[bon::bon]
+impl Build {
+    /// Build for the tests, the fields not given get plausible defaults
+    #[builder(finish_fn = build)]
+    pub fn test(
+        #[builder(default = 1)] id: i64,
+        #[builder(into, default = "default".to_string())] server: String,
+        #[builder(default = 1)] configuration: i64,
+        #[builder(into, default = "root/app".to_string())] configuration_path: String,
+        #[builder(default = 1)] requester: i64,
+        #[builder(into)] requester_name: Option<String>,
+        // defaults to `1.0.<id>`
+        #[builder(into)] version: Option<String>,
+        #[builder(default = BuildStatus::Successful)] status: BuildStatus,
+        // defaults to an hour ago plus a second per id, so that builds with higher ids begin later
+        begin_date: Option<OffsetDateTime>,
+        #[builder(default)] duration: i64,
+        #[builder(default)] read: bool,
+    ) -> Self {
+        Self {
+            id,
+            server,
+            configuration,
+            configuration_path,
+            master_node_address: "localhost:8810".to_string(),
+            requester,
+            requester_name,
+            canceller: None,
+            canceller_name: None,
+            version: version.unwrap_or_else(|| format!("1.0.{id}")),
+            status,
+            begin_date: begin_date.unwrap_or_else(|| {
+                OffsetDateTime::now_utc() - time::Duration::hours(1) + time::Duration::seconds(id)
+            }),
+            status_date: None,
+            duration,
+            wait_duration: 0,
+            read,
+        }
+    }
+}
+
+impl BuildTransition {
+    pub fn get_subject(&self) -> String {
+        let path = &self.build.configuration_path;
+        match self.kind {
+            TransitionKind::Broke => match &self.build.requester_name {
+                Some(requester) => format!("{path} broke, triggered by {requester}"),
+                None => format!("{path} broke"),
+            },
+            TransitionKind::Fixed => {
+                let failures = match self.failure_count {
+                    1 => "1 failure".to_string(),
+                    count => format!("{count} failures"),
+                };
+                match self.failing_duration {
+                    // minutes are precise enough to tell how long it was red
+                    Some(duration) if duration >= 60_000 => format!(
+                        "{path} fixed after {failures}, {} red",
+                        format_duration(duration - duration % 60_000).replace(' ', "")
+                    ),
+                    _ => format!("{path} fixed after {failures}"),
+                }
+            }
+        }
+    }
+}
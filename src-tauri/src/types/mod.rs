@@ -1,3 +1,8 @@
 pub mod alert;
 pub mod build;
+pub mod notification;
+pub mod quiet_hours;
+pub mod rule;
+pub mod server;
 pub mod settings;
+pub mod watch;
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use strum::{Display, EnumString};
+
+use crate::types::{alert::Alert, build::Build};
+
+/// Action type of the notifications of a single build, offering "Open build" and "Rebuild"
+#[cfg(mobile)]
+pub const BUILD_ACTION_TYPE: &str = "build";
+/// Action type of the notifications of a single alert, offering "Acknowledge"
+#[cfg(mobile)]
+pub const ALERT_ACTION_TYPE: &str = "alert";
+
+/// Action performed by the user on a notification
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Copy, PartialEq, Eq, EnumString, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum NotificationAction {
+    /// The notification itself was clicked
+    Open,
+    OpenBuild,
+    Rebuild,
+    Acknowledge,
+}
+
+/// Build or alert a notification is about, stored in the extra payload of the notification. Ids
+/// are strings as in the bindings, numbers are still read from the notifications sent before.
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationTarget {
+    /// Name of the server of the build or alert, missing in the notifications sent before
+    /// several servers were supported
+    #[serde(default)]
+    pub server: Option<String>,
+    #[serde(default, with = "crate::serde::option_string_id")]
+    pub build_id: Option<i64>,
+    #[serde(default, with = "crate::serde::option_string_id")]
+    pub configuration_id: Option<i64>,
+    #[serde(default, with = "crate::serde::option_string_id")]
+    pub alert_id: Option<i64>,
+}
+
+impl NotificationTarget {
+    pub fn build(build: &Build) -> Self {
+        Self {
+            server: Some(build.server.clone()),
+            build_id: Some(build.id),
+            configuration_id: Some(build.configuration),
+            alert_id: None,
+        }
+    }
+
+    pub fn alert(alert: &Alert) -> Self {
+        Self {
+            server: Some(alert.server.clone()),
+            alert_id: Some(alert.id),
+            ..Default::default()
+        }
+    }
+
+    /// Buttons of the notification on desktop, along with their label, where clicking the
+    /// notification itself opens its target
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    pub fn desktop_actions(&self) -> Vec<(NotificationAction, &'static str)> {
+        if self.build_id.is_some() {
+            vec![(NotificationAction::Rebuild, "Rebuild")]
+        } else if self.alert_id.is_some() {
+            vec![(NotificationAction::Acknowledge, "Acknowledge")]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Action type of the notification, registered by the frontend on mobile platforms
+    #[cfg(mobile)]
+    pub fn action_type_id(&self) -> Option<&'static str> {
+        if self.build_id.is_some() {
+            Some(BUILD_ACTION_TYPE)
+        } else if self.alert_id.is_some() {
+            Some(ALERT_ACTION_TYPE)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_ids_as_strings() {
+        let target = NotificationTarget {
+            server: Some("ci".to_string()),
+            build_id: Some(42),
+            configuration_id: Some(7),
+            alert_id: None,
+        };
+        let json = serde_json::to_value(&target).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "server": "ci",
+                "buildId": "42",
+                "configurationId": "7",
+                "alertId": null,
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<NotificationTarget>(json).unwrap(),
+            target
+        );
+
+        // the notifications sent before carry numbers
+        let target: NotificationTarget =
+            serde_json::from_str(r#"{"buildId": 42, "configurationId": 7}"#).unwrap();
+        assert_eq!(target.build_id, Some(42));
+        assert_eq!(target.alert_id, None);
+    }
+
+    #[test]
+    fn test_action_ids() {
+        assert_eq!(NotificationAction::OpenBuild.to_string(), "open_build");
+        assert_eq!(
+            "acknowledge".parse::<NotificationAction>().unwrap(),
+            NotificationAction::Acknowledge
+        );
+    }
+}
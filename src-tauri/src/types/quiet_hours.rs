@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use time::{OffsetDateTime, Time, macros::format_description};
+
+use crate::utils::times::to_local;
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl From<time::Weekday> for Weekday {
+    fn from(weekday: time::Weekday) -> Self {
+        match weekday {
+            time::Weekday::Monday => Weekday::Monday,
+            time::Weekday::Tuesday => Weekday::Tuesday,
+            time::Weekday::Wednesday => Weekday::Wednesday,
+            time::Weekday::Thursday => Weekday::Thursday,
+            time::Weekday::Friday => Weekday::Friday,
+            time::Weekday::Saturday => Weekday::Saturday,
+            time::Weekday::Sunday => Weekday::Sunday,
+        }
+    }
+}
+
+/// What happens to the notifications sent during quiet hours
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuietHoursMode {
+    /// Notifications are dropped
+    #[default]
+    Suppress,
+    /// Notifications are collapsed into a single digest delivered when quiet hours end
+    Digest,
+}
+
+/// Quiet time range starting on the given days, `start` and `end` are `HH:MM` times. A range
+/// ending before it starts runs past midnight, e.g. `22:00` to `07:00`, and a range ending
+/// when it starts lasts the whole day.
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct QuietPeriod {
+    pub days: Vec<Weekday>,
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct QuietHours {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub mode: QuietHoursMode,
+    #[serde(default)]
+    pub periods: Vec<QuietPeriod>,
+}
+
+fn parse_time(time: &str) -> Result<Time, String> {
+    Time::parse(time.trim(), format_description!("[hour]:[minute]"))
+        .map_err(|e| format!("Invalid quiet hours time '{time}': {e}"))
+}
+
+impl QuietPeriod {
+    fn contains(&self, date_time: OffsetDateTime) -> Result<bool, String> {
+        let start = parse_time(&self.start)?;
+        let end = parse_time(&self.end)?;
+        let time = date_time.time();
+        let today = Weekday::from(date_time.weekday());
+        let yesterday = Weekday::from(date_time.weekday().previous());
+
+        Ok(if start < end {
+            self.days.contains(&today) && start <= time && time < end
+        } else if start > end {
+            (self.days.contains(&today) && time >= start)
+                || (self.days.contains(&yesterday) && time < end)
+        } else {
+            self.days.contains(&today)
+        })
+    }
+}
+
+impl QuietHours {
+    /// Checks the times of the periods, quiet hours with an invalid time are not saved
+    pub fn validate(&self) -> Result<(), String> {
+        for period in &self.periods {
+            parse_time(&period.start)?;
+            parse_time(&period.end)?;
+        }
+        Ok(())
+    }
+
+    /// Whether notifications are quiet at the given time, the periods are in the local time
+    /// zone
+    pub fn is_quiet(&self, now: OffsetDateTime) -> bool {
+        self.is_quiet_at(to_local(now))
+    }
+
+    fn is_quiet_at(&self, local: OffsetDateTime) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        self.periods
+            .iter()
+            .any(|period| match period.contains(local) {
+                Ok(contains) => contains,
+                Err(e) => {
+                    tracing::warn!("Skipping quiet hours period: {e}");
+                    false
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    fn quiet_hours(days: Vec<Weekday>, start: &str, end: &str) -> QuietHours {
+        QuietHours {
+            enabled: true,
+            mode: QuietHoursMode::Suppress,
+            periods: vec![QuietPeriod {
+                days,
+                start: start.to_string(),
+                end: end.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_is_quiet_within_day() {
+        let quiet_hours = quiet_hours(vec![Weekday::Saturday], "12:00", "14:00");
+        // 2026-01-17 is a saturday
+        assert!(quiet_hours.is_quiet_at(datetime!(2026-01-17 12:30 +02:00)));
+        assert!(!quiet_hours.is_quiet_at(datetime!(2026-01-17 14:00 +02:00)));
+        assert!(!quiet_hours.is_quiet_at(datetime!(2026-01-18 12:30 +02:00)));
+    }
+
+    #[test]
+    fn test_is_quiet_past_midnight() {
+        let quiet_hours = quiet_hours(vec![Weekday::Friday], "22:00", "07:00");
+        assert!(quiet_hours.is_quiet_at(datetime!(2026-01-16 23:00 +02:00)));
+        assert!(quiet_hours.is_quiet_at(datetime!(2026-01-17 06:59 +02:00)));
+        assert!(!quiet_hours.is_quiet_at(datetime!(2026-01-17 07:00 +02:00)));
+        assert!(!quiet_hours.is_quiet_at(datetime!(2026-01-16 06:00 +02:00)));
+    }
+
+    #[test]
+    fn test_disabled_or_invalid_quiet_hours() {
+        let mut schedule = quiet_hours(vec![Weekday::Saturday], "00:00", "00:00");
+        assert!(schedule.is_quiet_at(datetime!(2026-01-17 18:00 +02:00)));
+
+        schedule.enabled = false;
+        assert!(!schedule.is_quiet_at(datetime!(2026-01-17 18:00 +02:00)));
+
+        let schedule = quiet_hours(vec![Weekday::Saturday], "25:00", "07:00");
+        assert!(!schedule.is_quiet_at(datetime!(2026-01-17 18:00 +02:00)));
+    }
+
+    #[test]
+    fn test_validate_quiet_hours() {
+        assert!(
+            quiet_hours(vec![Weekday::Friday], "22:00", "07:00")
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            quiet_hours(vec![Weekday::Friday], "22:00", "7am")
+                .validate()
+                .is_err()
+        );
+        assert!(
+            quiet_hours(vec![Weekday::Friday], "24:00", "07:00")
+                .validate()
+                .is_err()
+        );
+    }
+}
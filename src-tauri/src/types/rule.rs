@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::types::{
+    alert::{AlertCategory, AlertPriority},
+    build::BuildStatus,
+};
+
+/// What happens to the builds or alerts matched by a notification rule
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Recorded in the history and notified
+    #[default]
+    Notify,
+    /// Recorded in the history without notification
+    Silent,
+    /// Neither recorded nor notified
+    Ignore,
+}
+
+/// Pattern matched against the whole configuration path of a build
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PathPattern {
+    /// `*` matches within a path segment, `**` matches across segments and `?` matches one
+    /// character, e.g. `root/**/release`
+    Glob(String),
+    Regex(String),
+}
+
+/// Conditions of a rule on builds, unset conditions match every build
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct BuildRuleFilter {
+    #[serde(default)]
+    pub configuration_path: Option<PathPattern>,
+    #[serde(default)]
+    pub statuses: Vec<BuildStatus>,
+    /// Requester name, case insensitive
+    #[serde(default)]
+    pub requester: Option<String>,
+    /// Only matches the builds requested by the user of the settings
+    #[serde(default)]
+    pub only_mine: bool,
+}
+
+/// Conditions of a rule on alerts, unset conditions match every alert
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct AlertRuleFilter {
+    #[serde(default)]
+    pub priorities: Vec<AlertPriority>,
+    #[serde(default)]
+    pub categories: Vec<AlertCategory>,
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleFilter {
+    Build(BuildRuleFilter),
+    Alert(AlertRuleFilter),
+}
+
+/// User defined rule deciding how matching builds or alerts are notified, the first enabled
+/// matching rule applies
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct NotificationRule {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub filter: RuleFilter,
+    #[serde(default)]
+    pub action: RuleAction,
+}
+
+fn default_enabled() -> bool {
+    true
+}
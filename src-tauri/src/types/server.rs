@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use time::OffsetDateTime;
+
+use crate::services::quickbuild::{QuickBuildError, QuickBuildErrorKind};
+
+/// QuickBuild server polled by the tray monitor, with its own credentials
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct ServerProfile {
+    /// Unique name of the server, builds and alerts are tagged with it
+    pub name: String,
+    #[serde(default)]
+    pub server_url: String,
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub token: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ServerProfile {
+    pub fn is_configured(&self) -> bool {
+        !self.name.is_empty()
+            && !self.server_url.is_empty()
+            && !self.user.is_empty()
+            && !self.token.is_empty()
+    }
+}
+
+/// Polling status of a server, reported along with the build and alert history
+#[derive(Serialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    pub name: String,
+    pub error: Option<String>,
+    pub error_kind: Option<QuickBuildErrorKind>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde::option_four_year_iso8601"
+    )]
+    pub last_polling_time: Option<OffsetDateTime>,
+    /// Number of polls in a row that failed, 0 when the last poll succeeded
+    pub consecutive_failures: u32,
+    /// When the poller retries after failures, only set while polling is failing
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde::option_four_year_iso8601"
+    )]
+    pub next_retry_time: Option<OffsetDateTime>,
+}
+
+/// Outcome of a check of the connection test
+#[derive(Serialize, Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// Not run, as an earlier check failed
+    Skipped,
+}
+
+/// Single check of the connection test, with the error when it failed
+#[derive(Serialize, Type, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionCheck {
+    pub status: CheckStatus,
+    pub error: Option<String>,
+    pub error_kind: Option<QuickBuildErrorKind>,
+}
+
+impl ConnectionCheck {
+    pub fn passed() -> Self {
+        Self {
+            status: CheckStatus::Passed,
+            error: None,
+            error_kind: None,
+        }
+    }
+
+    pub fn failed(error: &QuickBuildError) -> Self {
+        Self {
+            status: CheckStatus::Failed,
+            error: Some(error.to_string()),
+            error_kind: Some(error.kind()),
+        }
+    }
+
+    pub fn skipped() -> Self {
+        Self {
+            status: CheckStatus::Skipped,
+            error: None,
+            error_kind: None,
+        }
+    }
+
+    pub fn is_passed(&self) -> bool {
+        self.status == CheckStatus::Passed
+    }
+}
+
+/// Diagnostic of the connection to a QuickBuild server, run from the settings before they
+/// are saved
+#[derive(Serialize, Type, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDiagnostic {
+    /// The server URL answers HTTP requests
+    pub reachable: ConnectionCheck,
+    /// The user and the token are accepted
+    pub authenticated: ConnectionCheck,
+    /// The server exposes the notifications API polled by the tray monitor
+    pub notifications_endpoint: ConnectionCheck,
+    pub version: ConnectionCheck,
+    /// Version reported by the server
+    pub server_version: Option<String>,
+    /// Whether every check passed
+    pub ok: bool,
+}
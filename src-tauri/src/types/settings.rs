@@ -1,8 +1,17 @@
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use specta::Type;
-use tauri::{AppHandle, Url, Wry};
+use tauri::{AppHandle, Manager, Url, Wry};
 use tauri_plugin_store::StoreExt;
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    constants::{DEFAULT_SERVER_NAME, MAX_HISTORY_AGE_DAYS, MAX_STORE_ROWS, REDACTED_TOKEN},
+    services::{credentials::CredentialStore, database::Retention},
+    types::{
+        quiet_hours::QuietHours, rule::NotificationRule, server::ServerProfile, watch::WatchList,
+    },
+};
 
 #[derive(Default, Debug, Copy, Clone, Serialize, Deserialize, Type)]
 #[serde(rename_all = "snake_case")]
@@ -18,20 +27,50 @@ pub enum AppTheme {
 pub struct AppSettings {
     #[serde(default = "default_enable_notifications")]
     pub enable_notifications: bool,
+    /// Maximum number of builds and alerts of each server kept in the history
     #[serde(default = "default_notifications_total")]
     pub notifications_total: u32,
+    /// Builds and alerts older than this are removed from the history, 0 keeps them. At most
+    /// [`MAX_HISTORY_AGE_DAYS`].
+    #[serde(default)]
+    pub history_max_age_days: u32,
     #[serde(default = "default_theme")]
     pub theme: AppTheme,
     #[serde(default)]
     pub server_url: String,
     #[serde(default)]
     pub user: String,
+    /// Kept in the credential store, never saved in the settings store
     #[serde(default)]
     pub token: String,
+    /// Servers polled, with their own credentials. When empty, the single server of
+    /// `server_url`, `user` and `token` is polled as the `default` server.
+    #[serde(default)]
+    pub servers: Vec<ServerProfile>,
     #[serde(default = "default_poll_interval_in_secs")]
     pub poll_interval_in_secs: u32,
     #[serde(default)]
     pub paused: bool,
+    /// When a timed pause ends, polling resumes automatically after this time
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde::option_four_year_iso8601"
+    )]
+    pub paused_until: Option<OffsetDateTime>,
+    /// Decide which builds and alerts are notified, recorded silently or ignored
+    #[serde(default)]
+    pub notification_rules: Vec<NotificationRule>,
+    #[serde(default)]
+    pub quiet_hours: QuietHours,
+    /// Builds and alerts are notified as a single summary every this many minutes, 0 notifies
+    /// them as soon as they are fetched
+    #[serde(default)]
+    pub digest_window_in_mins: u32,
+    /// Configurations the user cares about, optionally the only ones notified and shown in
+    /// the tray status
+    #[serde(default)]
+    pub watch_list: WatchList,
 }
 
 fn default_enable_notifications() -> bool {
@@ -39,7 +78,7 @@ fn default_enable_notifications() -> bool {
 }
 
 fn default_notifications_total() -> u32 {
-    100
+    MAX_STORE_ROWS as u32
 }
 
 fn default_theme() -> AppTheme {
@@ -55,12 +94,19 @@ impl Default for AppSettings {
         Self {
             enable_notifications: default_enable_notifications(),
             notifications_total: default_notifications_total(),
+            history_max_age_days: 0,
             theme: default_theme(),
             server_url: "".to_string(),
             user: "".to_string(),
             token: "".to_string(),
+            servers: Vec::new(),
             poll_interval_in_secs: default_poll_interval_in_secs(),
             paused: false,
+            paused_until: None,
+            notification_rules: Vec::new(),
+            quiet_hours: QuietHours::default(),
+            digest_window_in_mins: 0,
+            watch_list: WatchList::default(),
         }
     }
 }
@@ -69,14 +115,143 @@ const STORE_FILE_NAME: &str = "settings.json";
 
 impl AppSettings {
     pub fn is_configured(&self) -> bool {
-        !self.server_url.is_empty() && !self.user.is_empty() && !self.token.is_empty()
+        !self.get_servers().is_empty()
+    }
+
+    /// Enabled and configured servers to poll
+    pub fn get_servers(&self) -> Vec<ServerProfile> {
+        if self.servers.is_empty() {
+            let server = ServerProfile {
+                name: DEFAULT_SERVER_NAME.to_string(),
+                server_url: self.server_url.clone(),
+                user: self.user.clone(),
+                token: self.token.clone(),
+                enabled: true,
+            };
+            return Some(server)
+                .filter(ServerProfile::is_configured)
+                .into_iter()
+                .collect();
+        }
+
+        self.servers
+            .iter()
+            .filter(|server| server.enabled && server.is_configured())
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_server(&self, name: &str) -> Result<ServerProfile, String> {
+        self.get_servers()
+            .into_iter()
+            .find(|server| server.name == name)
+            .ok_or_else(|| format!("QuickBuild server {name} is not configured"))
+    }
+
+    /// Whether a timed pause has ended and polling should resume
+    pub fn is_pause_expired(&self, now: OffsetDateTime) -> bool {
+        self.paused && self.paused_until.is_some_and(|until| until <= now)
+    }
+
+    /// How much build and alert history to keep
+    pub fn get_retention(&self) -> Retention {
+        Retention {
+            max_rows: self.notifications_total.max(1) as usize,
+            max_age_days: Some(self.history_max_age_days.min(MAX_HISTORY_AGE_DAYS))
+                .filter(|days| *days > 0),
+        }
+    }
+
+    /// Window over which builds and alerts are collected into a digest, `None` when digests
+    /// are disabled
+    pub fn get_digest_window(&self) -> Option<Duration> {
+        (self.digest_window_in_mins > 0)
+            .then(|| Duration::minutes(self.digest_window_in_mins as i64))
     }
 
+    /// Dashboard of the first server
     pub fn get_dashboard_url(&self) -> Url {
-        format!("{}/lite", self.server_url).parse().unwrap()
+        let server_url = self
+            .get_servers()
+            .into_iter()
+            .next()
+            .map_or_else(|| self.server_url.clone(), |server| server.server_url);
+        format!("{server_url}/lite").parse().unwrap()
     }
 
-    pub fn get(app: &AppHandle<Wry>) -> Result<Self, String> {
+    pub fn get_build_url(&self, server: &str, build_id: i64) -> Result<Url, String> {
+        format!("{}/build/{build_id}", self.get_server(server)?.server_url)
+            .parse()
+            .map_err(|e| format!("Invalid build URL: {e}"))
+    }
+
+    /// Takes the settings edited by their own commands from the saved settings, as the copy
+    /// sent along by the preferences form may be stale
+    pub fn keep_saved_fields(&mut self, saved: &Self) {
+        self.notification_rules = saved.notification_rules.clone();
+        self.quiet_hours = saved.quiet_hours.clone();
+        self.watch_list = saved.watch_list.clone();
+        // the form only pauses or resumes, the end of the pause is set from the tray
+        self.paused_until = saved.paused_until.filter(|_| self.paused);
+    }
+
+    /// Access tokens by the name of their server, the single server token only counts when no
+    /// server profile is defined
+    fn tokens(&self) -> Vec<(&str, &str)> {
+        if self.servers.is_empty() {
+            vec![(DEFAULT_SERVER_NAME, self.token.as_str())]
+        } else {
+            self.servers
+                .iter()
+                .map(|server| (server.name.as_str(), server.token.as_str()))
+                .collect()
+        }
+    }
+
+    fn tokens_mut(&mut self) -> Vec<(String, &mut String)> {
+        if self.servers.is_empty() {
+            vec![(DEFAULT_SERVER_NAME.to_string(), &mut self.token)]
+        } else {
+            self.servers
+                .iter_mut()
+                .map(|server| (server.name.clone(), &mut server.token))
+                .collect()
+        }
+    }
+
+    /// Settings sent to the frontend, with the saved tokens replaced by [`REDACTED_TOKEN`]
+    pub fn redacted(&self) -> Self {
+        let redact = |token: &str| {
+            if token.is_empty() {
+                String::new()
+            } else {
+                REDACTED_TOKEN.to_string()
+            }
+        };
+        let mut settings = self.clone();
+        settings.token = redact(&self.token);
+        for server in &mut settings.servers {
+            server.token = redact(&server.token);
+        }
+        settings
+    }
+
+    fn without_tokens(&self) -> Self {
+        let mut settings = self.clone();
+        settings.token.clear();
+        for server in &mut settings.servers {
+            server.token.clear();
+        }
+        settings
+    }
+
+    /// Whether the settings store still has tokens, as saved by previous versions
+    fn has_plaintext_tokens(&self) -> bool {
+        !self.token.is_empty() || self.servers.iter().any(|server| !server.token.is_empty())
+    }
+
+    /// Settings of the store, without their tokens
+    fn load(app: &AppHandle<Wry>) -> Result<Self, String> {
         match app.store(STORE_FILE_NAME).map(|s| s.get("settings")) {
             Ok(Some(store)) => match serde_json::from_value(store) {
                 Ok(settings) => Ok(settings),
@@ -86,12 +261,30 @@ impl AppSettings {
         }
     }
 
+    /// Settings of the store, along with their tokens from the credential store
+    pub fn get(app: &AppHandle<Wry>) -> Result<Self, String> {
+        let mut settings = Self::load(app)?;
+        let credentials = app.state::<CredentialStore>();
+        for (server, token) in settings.tokens_mut() {
+            // tokens which could not be moved out of the store yet are used as they are
+            if !token.is_empty() {
+                continue;
+            }
+            match credentials.get_token(&server) {
+                Ok(saved) => *token = saved.unwrap_or_default(),
+                Err(e) => tracing::error!("Failed to get token of {server}: {e}"),
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Updates the settings of the store, their tokens are left as they are
     pub fn update(app: &AppHandle<Wry>, update: impl FnOnce(&mut Self)) -> Result<(), String> {
         let Ok(store) = app.store(STORE_FILE_NAME) else {
             return Err("App settings store not found".to_string());
         };
 
-        let mut settings = Self::get(app)?;
+        let mut settings = Self::load(app)?;
         update(&mut settings);
         store.set("settings", json!(settings));
         store
@@ -99,16 +292,55 @@ impl AppSettings {
             .map_err(|e| format!("Failed to save app settings: {e}"))
     }
 
+    /// Saves the settings, with their tokens in the credential store. A redacted token keeps
+    /// the saved one and is rejected for a server without saved token, the tokens of the
+    /// removed servers are deleted.
     pub fn save(&self, app: &AppHandle<Wry>) -> Result<(), String> {
         let Ok(store) = app.store(STORE_FILE_NAME) else {
             return Err("App settings store not found".to_string());
         };
 
-        store.set("settings", json!(self));
+        let credentials = app.state::<CredentialStore>();
+        let tokens = self.tokens();
+        // checked before anything is saved, as a renamed server comes with the redacted token
+        // of its previous name
+        for (server, token) in &tokens {
+            if *token == REDACTED_TOKEN && credentials.get_token(server)?.is_none() {
+                return Err(format!(
+                    "No token is saved for server {server}, please enter its token"
+                ));
+            }
+        }
+        for (server, token) in &tokens {
+            match *token {
+                REDACTED_TOKEN => {}
+                "" => credentials.delete_token(server)?,
+                token => credentials.set_token(server, token)?,
+            }
+        }
+        for (server, _) in Self::load(app)?.tokens() {
+            if !tokens.iter().any(|(name, _)| *name == server) {
+                credentials.delete_token(server)?;
+            }
+        }
+
+        store.set("settings", json!(self.without_tokens()));
         store
             .save()
             .map_err(|e| format!("Failed to save app settings: {e}"))
     }
+
+    /// Moves the tokens saved in the settings store by previous versions into the credential
+    /// store
+    pub fn migrate_tokens(app: &AppHandle<Wry>) -> Result<(), String> {
+        let settings = Self::load(app)?;
+        if !settings.has_plaintext_tokens() {
+            return Ok(());
+        }
+
+        tracing::info!("Moving access tokens out of the settings store");
+        settings.save(app)
+    }
 }
 
 // pub fn init(app: &AppHandle<Wry>) {
@@ -128,3 +360,100 @@ impl AppSettings {
 
 //     tracing::info!("App settings initialized");
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::watch::WatchedConfiguration;
+
+    fn settings_with_servers() -> AppSettings {
+        let server = |name: &str, token: &str| ServerProfile {
+            name: name.to_string(),
+            server_url: format!("http://{name}:8810"),
+            user: "admin".to_string(),
+            token: token.to_string(),
+            enabled: true,
+        };
+        AppSettings {
+            token: "legacy".to_string(),
+            servers: vec![server("ci", "secret"), server("staging", "")],
+            ..AppSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_tokens_by_server() {
+        let settings = AppSettings {
+            token: "secret".to_string(),
+            ..AppSettings::default()
+        };
+        assert_eq!(settings.tokens(), vec![(DEFAULT_SERVER_NAME, "secret")]);
+
+        // the single server token is ignored once server profiles are defined
+        assert_eq!(
+            settings_with_servers().tokens(),
+            vec![("ci", "secret"), ("staging", "")]
+        );
+    }
+
+    #[test]
+    fn test_retention_caps_history_age() {
+        let settings = AppSettings {
+            history_max_age_days: u32::MAX,
+            ..AppSettings::default()
+        };
+        assert_eq!(
+            settings.get_retention().max_age_days,
+            Some(MAX_HISTORY_AGE_DAYS)
+        );
+        assert_eq!(AppSettings::default().get_retention().max_age_days, None);
+    }
+
+    #[test]
+    fn test_keep_saved_fields() {
+        let saved = AppSettings {
+            paused: true,
+            paused_until: Some(OffsetDateTime::UNIX_EPOCH),
+            watch_list: WatchList {
+                configurations: vec![WatchedConfiguration::Id {
+                    id: 1,
+                    server: None,
+                }],
+                ..WatchList::default()
+            },
+            ..AppSettings::default()
+        };
+
+        let mut settings = AppSettings {
+            paused: true,
+            ..AppSettings::default()
+        };
+        settings.keep_saved_fields(&saved);
+        assert_eq!(
+            settings.watch_list.configurations,
+            saved.watch_list.configurations
+        );
+        assert_eq!(settings.paused_until, saved.paused_until);
+
+        // resuming from the form ends the pause
+        settings.paused = false;
+        settings.keep_saved_fields(&saved);
+        assert_eq!(settings.paused_until, None);
+    }
+
+    #[test]
+    fn test_redacted_and_stripped_tokens() {
+        let settings = settings_with_servers();
+        assert!(settings.has_plaintext_tokens());
+
+        let redacted = settings.redacted();
+        assert_eq!(redacted.token, REDACTED_TOKEN);
+        assert_eq!(redacted.servers[0].token, REDACTED_TOKEN);
+        assert_eq!(redacted.servers[1].token, "");
+        assert_eq!(redacted.servers[0].server_url, "http://ci:8810");
+
+        let stripped = settings.without_tokens();
+        assert!(!stripped.has_plaintext_tokens());
+        assert!(!json!(stripped).to_string().contains("secret"));
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use time::OffsetDateTime;
+
+use crate::types::{build::Build, rule::PathPattern};
+
+/// Configuration of the watch list, matched by its id or its path. Ids are only unique on
+/// their server, an id without server matches the configurations of every server, as saved
+/// before servers were named.
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum WatchedConfiguration {
+    Id {
+        id: i64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        server: Option<String>,
+    },
+    Path {
+        path: PathPattern,
+    },
+}
+
+/// Configurations the user cares about. Builds of other configurations are still recorded in
+/// the history, and the restrictions only apply once the list has configurations.
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct WatchList {
+    #[serde(default)]
+    pub configurations: Vec<WatchedConfiguration>,
+    /// Only notifies the builds of the watched configurations
+    #[serde(default)]
+    pub notify_only_watched: bool,
+    /// Only the builds of the watched configurations decide the tray status
+    #[serde(default)]
+    pub status_only_watched: bool,
+}
+
+/// Current status of a watched configuration, told by its latest build on the server
+#[derive(Serialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigurationStatus {
+    /// Name of the server of the configuration
+    pub server: String,
+    pub configuration: i64,
+    pub configuration_path: String,
+    /// `None` when the configuration was never built
+    pub latest_build: Option<Build>,
+    #[serde(with = "crate::serde::four_year_iso8601")]
+    pub checked_time: OffsetDateTime,
+    /// Why the latest build could not be fetched in the last check
+    pub error: Option<String>,
+}
@@ -1 +1,2 @@
 pub mod platform;
+pub mod times;
@@ -1,10 +1,14 @@
+// Allow unused code - not every helper is used by the app yet
+#![allow(dead_code)]
+
 use std::{
     num::NonZeroU8,
     time::{SystemTime, UNIX_EPOCH},
 };
 
+use chrono::TimeZone;
 use time::{
-    OffsetDateTime, PrimitiveDateTime,
+    Duration, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset,
     format_description::{
         self,
         well_known::{
@@ -88,6 +92,39 @@ pub fn now() -> PrimitiveDateTime {
     odt_to_pdt(utc_date_time)
 }
 
+/// Offset of the local time zone at the given instant, following daylight saving time.
+///
+/// `OffsetDateTime::now_local` refuses to read the offset once the process runs several threads
+/// on Linux and macOS, so it is looked up with chrono which reads the system time zone instead.
+fn local_offset_at(date_time: OffsetDateTime) -> UtcOffset {
+    chrono::Local
+        .timestamp_opt(date_time.unix_timestamp(), 0)
+        .single()
+        .and_then(|local| UtcOffset::from_whole_seconds(local.offset().local_minus_utc()).ok())
+        .unwrap_or(UtcOffset::UTC)
+}
+
+/// Converts to the local time zone, with the offset in effect at that instant
+pub fn to_local(date_time: OffsetDateTime) -> OffsetDateTime {
+    date_time.to_offset(local_offset_at(date_time))
+}
+
+/// Local midnight starting the day after `date_time`
+pub fn start_of_next_local_day(date_time: OffsetDateTime) -> OffsetDateTime {
+    start_of_next_day(date_time, local_offset_at)
+}
+
+/// Midnight starting the day after `date_time`, in the time zone giving the offset of an instant
+fn start_of_next_day(
+    date_time: OffsetDateTime,
+    offset_at: impl Fn(OffsetDateTime) -> UtcOffset,
+) -> OffsetDateTime {
+    let midnight = (date_time.to_offset(offset_at(date_time)) + Duration::days(1))
+        .replace_time(Time::MIDNIGHT);
+    // The offset of the next day differs when daylight saving time starts or ends overnight
+    midnight.replace_offset(offset_at(midnight))
+}
+
 /// Convert from OffsetDateTime to PrimitiveDateTime
 pub fn odt_to_pdt(offset_time: OffsetDateTime) -> PrimitiveDateTime {
     PrimitiveDateTime::new(offset_time.date(), offset_time.time())
@@ -126,6 +163,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_start_of_next_day() {
+        let offset = |_| UtcOffset::from_hms(8, 0, 0).unwrap();
+        assert_eq!(
+            start_of_next_day(datetime!(2026-01-17 10:08:23 +08:00), offset),
+            datetime!(2026-01-18 00:00:00 +08:00)
+        );
+        assert_eq!(
+            start_of_next_day(datetime!(2026-01-17 20:00:00 UTC), offset),
+            datetime!(2026-01-19 00:00:00 +08:00)
+        );
+    }
+
+    #[test]
+    fn test_start_of_next_day_across_daylight_saving_time() {
+        // Daylight saving time starting at 2026-03-29 01:00 UTC, as in Central Europe
+        let offset = |date_time: OffsetDateTime| {
+            if date_time < datetime!(2026-03-29 01:00 UTC) {
+                UtcOffset::from_hms(1, 0, 0).unwrap()
+            } else {
+                UtcOffset::from_hms(2, 0, 0).unwrap()
+            }
+        };
+        assert_eq!(
+            start_of_next_day(datetime!(2026-03-28 12:00 UTC), offset),
+            datetime!(2026-03-29 00:00 +01:00)
+        );
+        assert_eq!(
+            start_of_next_day(datetime!(2026-03-29 12:00 UTC), offset),
+            datetime!(2026-03-30 00:00 +02:00)
+        );
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(0), "0ms");
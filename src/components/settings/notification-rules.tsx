@@ -0,0 +1,500 @@
+import { zodResolver } from "@hookform/resolvers/zod";
+import { PlusIcon, Trash2Icon } from "lucide-react";
+import { useEffect } from "react";
+import {
+  type Control,
+  Controller,
+  useFieldArray,
+  useForm,
+  useWatch,
+} from "react-hook-form";
+import { z } from "zod";
+import { Button } from "@/components/ui/button";
+import {
+  Field,
+  FieldError,
+  FieldGroup,
+  FieldLabel,
+  FieldLegend,
+  FieldSet,
+} from "@/components/ui/field";
+import { Input } from "@/components/ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import { Spinner } from "@/components/ui/spinner";
+import { Switch } from "@/components/ui/switch";
+import { ToggleGroup, ToggleGroupItem } from "@/components/ui/toggle-group";
+import type { NotificationRule } from "@/lib/bindings";
+import {
+  useNotificationRules,
+  useSaveNotificationRules,
+} from "@/services/rules";
+
+const BUILD_STATUSES = [
+  "SUCCESSFUL",
+  "RECOMMENDED",
+  "FAILED",
+  "CANCELLED",
+  "TIMEOUT",
+  "RUNNING",
+] as const;
+const ALERT_PRIORITIES = ["LOW", "MEDIUM", "HIGH"] as const;
+const ALERT_CATEGORIES = ["SYSTEM", "METRIC"] as const;
+
+// Rules are edited flattened, the filter of the other target is dropped on
+// save
+const ruleSchema = z.object({
+  name: z.string().trim().min(1, "Name is required"),
+  enabled: z.boolean(),
+  target: z.enum(["build", "alert"]),
+  action: z.enum(["notify", "silent", "ignore"]),
+  pattern_kind: z.enum(["glob", "regex"]),
+  pattern: z.string(),
+  statuses: z.array(z.enum(BUILD_STATUSES)),
+  requester: z.string(),
+  only_mine: z.boolean(),
+  priorities: z.array(z.enum(ALERT_PRIORITIES)),
+  categories: z.array(z.enum(ALERT_CATEGORIES)),
+});
+
+const formSchema = z.object({
+  rules: z.array(ruleSchema),
+});
+
+type RuleValues = z.infer<typeof ruleSchema>;
+type RulesFormValues = z.infer<typeof formSchema>;
+
+const NEW_RULE: RuleValues = {
+  name: "",
+  enabled: true,
+  target: "build",
+  action: "notify",
+  pattern_kind: "glob",
+  pattern: "",
+  statuses: [],
+  requester: "",
+  only_mine: false,
+  priorities: [],
+  categories: [],
+};
+
+const toRuleValues = (rule: NotificationRule): RuleValues => {
+  const build = "build" in rule.filter ? rule.filter.build : null;
+  const alert = "alert" in rule.filter ? rule.filter.alert : null;
+  const path = build?.configuration_path;
+  return {
+    name: rule.name ?? "",
+    enabled: rule.enabled ?? true,
+    target: build ? "build" : "alert",
+    action: rule.action ?? "notify",
+    pattern_kind: path && "regex" in path ? "regex" : "glob",
+    pattern: path ? ("regex" in path ? path.regex : path.glob) : "",
+    statuses: build?.statuses ?? [],
+    requester: build?.requester ?? "",
+    only_mine: build?.only_mine ?? false,
+    priorities: alert?.priorities ?? [],
+    categories: alert?.categories ?? [],
+  };
+};
+
+const toRule = (values: RuleValues): NotificationRule => {
+  const pattern = values.pattern.trim();
+  return {
+    name: values.name,
+    enabled: values.enabled,
+    action: values.action,
+    filter:
+      values.target === "build"
+        ? {
+            build: {
+              configuration_path: pattern
+                ? values.pattern_kind === "regex"
+                  ? { regex: pattern }
+                  : { glob: pattern }
+                : null,
+              statuses: values.statuses,
+              requester: values.requester.trim() || null,
+              only_mine: values.only_mine,
+            },
+          }
+        : {
+            alert: {
+              priorities: values.priorities,
+              categories: values.categories,
+            },
+          },
+  };
+};
+
+export const NotificationRules = () => {
+  const { data: rules } = useNotificationRules();
+  const saveRules = useSaveNotificationRules();
+
+  const form = useForm<RulesFormValues>({
+    resolver: zodResolver(formSchema),
+    defaultValues: { rules: [] },
+  });
+
+  const { fields, append, remove } = useFieldArray({
+    control: form.control,
+    name: "rules",
+  });
+
+  useEffect(() => {
+    if (rules) {
+      form.reset({ rules: rules.map(toRuleValues) });
+    }
+  }, [rules, form]);
+
+  const onSubmit = async (data: RulesFormValues) => {
+    await saveRules.mutateAsync(data.rules.map(toRule)).catch(() => {
+      // Reported by the mutation
+    });
+  };
+
+  return (
+    <form
+      className="space-y-4"
+      id="form-notification-rules"
+      onSubmit={form.handleSubmit(onSubmit)}
+    >
+      <FieldSet>
+        <FieldLegend className="font-semibold">Notification Rules</FieldLegend>
+        <p className="text-muted-foreground text-sm">
+          The first enabled rule matching a build or an alert decides whether
+          it is notified, recorded silently or ignored.
+        </p>
+        {fields.map((field, index) => (
+          <NotificationRuleFields
+            control={form.control}
+            index={index}
+            key={field.id}
+            onRemove={() => remove(index)}
+          />
+        ))}
+        <Button
+          onClick={() => append(NEW_RULE)}
+          type="button"
+          variant="outline"
+        >
+          <PlusIcon className="size-4" />
+          Add Rule
+        </Button>
+      </FieldSet>
+      <Field className="justify-end" orientation="horizontal">
+        <Button
+          className="flex-1"
+          disabled={form.formState.isSubmitting}
+          form="form-notification-rules"
+          type="submit"
+        >
+          {form.formState.isSubmitting && (
+            <Spinner className="size-4 animate-spin" />
+          )}
+          Save Rules
+        </Button>
+      </Field>
+    </form>
+  );
+};
+
+const NotificationRuleFields = ({
+  control,
+  index,
+  onRemove,
+}: {
+  control: Control<RulesFormValues>;
+  index: number;
+  onRemove: () => void;
+}) => {
+  const id = `form-notification-rules-${index}`;
+  const target = useWatch({ control, name: `rules.${index}.target` });
+  return (
+    <FieldGroup className="gap-4 rounded-md border p-4">
+      <div className="flex items-center space-x-2">
+        <Controller
+          control={control}
+          name={`rules.${index}.enabled`}
+          render={({ field }) => (
+            <Field className="mr-auto">
+              <div className="flex items-center space-x-2">
+                <Switch
+                  checked={Boolean(field.value)}
+                  id={`${id}-enabled`}
+                  onCheckedChange={field.onChange}
+                  value={field.value ? "true" : "false"}
+                />
+                <FieldLabel
+                  className="flex-1 font-semibold"
+                  htmlFor={`${id}-enabled`}
+                >
+                  Enabled
+                </FieldLabel>
+              </div>
+            </Field>
+          )}
+        />
+        <Button
+          onClick={onRemove}
+          size="icon"
+          title="Remove rule"
+          type="button"
+          variant="ghost"
+        >
+          <Trash2Icon className="size-4" />
+        </Button>
+      </div>
+      <Controller
+        control={control}
+        name={`rules.${index}.name`}
+        render={({ field, fieldState }) => (
+          <Field data-invalid={fieldState.invalid}>
+            <FieldLabel className="font-semibold" htmlFor={`${id}-name`}>
+              Name
+            </FieldLabel>
+            <Input
+              {...field}
+              aria-invalid={fieldState.invalid}
+              autoComplete="off"
+              id={`${id}-name`}
+              placeholder="Failed release builds"
+            />
+            {fieldState.invalid && <FieldError errors={[fieldState.error]} />}
+          </Field>
+        )}
+      />
+      <div className="flex space-x-4">
+        <Controller
+          control={control}
+          name={`rules.${index}.target`}
+          render={({ field }) => (
+            <Field>
+              <FieldLabel className="font-semibold" htmlFor={`${id}-target`}>
+                Applies To
+              </FieldLabel>
+              <Select onValueChange={field.onChange} value={field.value}>
+                <SelectTrigger id={`${id}-target`}>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="build">Builds</SelectItem>
+                  <SelectItem value="alert">Alerts</SelectItem>
+                </SelectContent>
+              </Select>
+            </Field>
+          )}
+        />
+        <Controller
+          control={control}
+          name={`rules.${index}.action`}
+          render={({ field }) => (
+            <Field>
+              <FieldLabel className="font-semibold" htmlFor={`${id}-action`}>
+                Action
+              </FieldLabel>
+              <Select onValueChange={field.onChange} value={field.value}>
+                <SelectTrigger id={`${id}-action`}>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="notify">Notify</SelectItem>
+                  <SelectItem value="silent">Record silently</SelectItem>
+                  <SelectItem value="ignore">Ignore</SelectItem>
+                </SelectContent>
+              </Select>
+            </Field>
+          )}
+        />
+      </div>
+      {target === "build" ? (
+        <>
+          <div className="flex space-x-4">
+            <Controller
+              control={control}
+              name={`rules.${index}.pattern_kind`}
+              render={({ field }) => (
+                <Field className="w-32">
+                  <FieldLabel
+                    className="font-semibold"
+                    htmlFor={`${id}-pattern-kind`}
+                  >
+                    Match
+                  </FieldLabel>
+                  <Select onValueChange={field.onChange} value={field.value}>
+                    <SelectTrigger id={`${id}-pattern-kind`}>
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      <SelectItem value="glob">Glob</SelectItem>
+                      <SelectItem value="regex">Regex</SelectItem>
+                    </SelectContent>
+                  </Select>
+                </Field>
+              )}
+            />
+            <Controller
+              control={control}
+              name={`rules.${index}.pattern`}
+              render={({ field, fieldState }) => (
+                <Field data-invalid={fieldState.invalid}>
+                  <FieldLabel
+                    className="font-semibold"
+                    htmlFor={`${id}-pattern`}
+                  >
+                    Configuration Path (empty matches all)
+                  </FieldLabel>
+                  <Input
+                    {...field}
+                    aria-invalid={fieldState.invalid}
+                    autoComplete="off"
+                    id={`${id}-pattern`}
+                    placeholder="root/releases/**"
+                  />
+                  {fieldState.invalid && (
+                    <FieldError errors={[fieldState.error]} />
+                  )}
+                </Field>
+              )}
+            />
+          </div>
+          <Controller
+            control={control}
+            name={`rules.${index}.statuses`}
+            render={({ field }) => (
+              <Field>
+                <FieldLabel className="font-semibold">
+                  Statuses (none matches all)
+                </FieldLabel>
+                <ToggleGroup
+                  onValueChange={field.onChange}
+                  size="sm"
+                  type="multiple"
+                  value={field.value}
+                  variant="outline"
+                >
+                  {BUILD_STATUSES.map((status) => (
+                    <ToggleGroupItem
+                      className="capitalize"
+                      key={status}
+                      value={status}
+                    >
+                      {status.toLowerCase()}
+                    </ToggleGroupItem>
+                  ))}
+                </ToggleGroup>
+              </Field>
+            )}
+          />
+          <div className="flex items-end space-x-4">
+            <Controller
+              control={control}
+              name={`rules.${index}.requester`}
+              render={({ field }) => (
+                <Field>
+                  <FieldLabel
+                    className="font-semibold"
+                    htmlFor={`${id}-requester`}
+                  >
+                    Requester (empty matches all)
+                  </FieldLabel>
+                  <Input
+                    {...field}
+                    autoComplete="off"
+                    id={`${id}-requester`}
+                    placeholder="username"
+                  />
+                </Field>
+              )}
+            />
+            <Controller
+              control={control}
+              name={`rules.${index}.only_mine`}
+              render={({ field }) => (
+                <Field className="w-auto">
+                  <div className="flex h-9 items-center space-x-2">
+                    <Switch
+                      checked={Boolean(field.value)}
+                      id={`${id}-only-mine`}
+                      onCheckedChange={field.onChange}
+                      value={field.value ? "true" : "false"}
+                    />
+                    <FieldLabel
+                      className="font-semibold"
+                      htmlFor={`${id}-only-mine`}
+                    >
+                      Only Mine
+                    </FieldLabel>
+                  </div>
+                </Field>
+              )}
+            />
+          </div>
+        </>
+      ) : (
+        <>
+          <Controller
+            control={control}
+            name={`rules.${index}.priorities`}
+            render={({ field }) => (
+              <Field>
+                <FieldLabel className="font-semibold">
+                  Priorities (none matches all)
+                </FieldLabel>
+                <ToggleGroup
+                  onValueChange={field.onChange}
+                  size="sm"
+                  type="multiple"
+                  value={field.value}
+                  variant="outline"
+                >
+                  {ALERT_PRIORITIES.map((priority) => (
+                    <ToggleGroupItem
+                      className="capitalize"
+                      key={priority}
+                      value={priority}
+                    >
+                      {priority.toLowerCase()}
+                    </ToggleGroupItem>
+                  ))}
+                </ToggleGroup>
+              </Field>
+            )}
+          />
+          <Controller
+            control={control}
+            name={`rules.${index}.categories`}
+            render={({ field }) => (
+              <Field>
+                <FieldLabel className="font-semibold">
+                  Categories (none matches all)
+                </FieldLabel>
+                <ToggleGroup
+                  onValueChange={field.onChange}
+                  size="sm"
+                  type="multiple"
+                  value={field.value}
+                  variant="outline"
+                >
+                  {ALERT_CATEGORIES.map((category) => (
+                    <ToggleGroupItem
+                      className="capitalize"
+                      key={category}
+                      value={category}
+                    >
+                      {category.toLowerCase()}
+                    </ToggleGroupItem>
+                  ))}
+                </ToggleGroup>
+              </Field>
+            )}
+          />
+        </>
+      )}
+    </FieldGroup>
+  );
+};
@@ -0,0 +1,278 @@
+import { zodResolver } from "@hookform/resolvers/zod";
+import { PlusIcon, Trash2Icon } from "lucide-react";
+import { useEffect } from "react";
+import {
+  type Control,
+  Controller,
+  useFieldArray,
+  useForm,
+} from "react-hook-form";
+import { z } from "zod";
+import { Button } from "@/components/ui/button";
+import {
+  Field,
+  FieldError,
+  FieldGroup,
+  FieldLabel,
+  FieldLegend,
+  FieldSet,
+} from "@/components/ui/field";
+import { Input } from "@/components/ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import { Spinner } from "@/components/ui/spinner";
+import { Switch } from "@/components/ui/switch";
+import { ToggleGroup, ToggleGroupItem } from "@/components/ui/toggle-group";
+import type { QuietPeriod } from "@/lib/bindings";
+import { useQuietHours, useSaveQuietHours } from "@/services/quiet-hours";
+
+const WEEKDAYS = [
+  "monday",
+  "tuesday",
+  "wednesday",
+  "thursday",
+  "friday",
+  "saturday",
+  "sunday",
+] as const;
+
+const TIME_PATTERN = /^([01]\d|2[0-3]):[0-5]\d$/;
+
+const periodSchema = z.object({
+  days: z.array(z.enum(WEEKDAYS)).min(1, "Select at least one day"),
+  start: z.string().regex(TIME_PATTERN, "Start must be a HH:MM time"),
+  end: z.string().regex(TIME_PATTERN, "End must be a HH:MM time"),
+});
+
+const formSchema = z.object({
+  enabled: z.boolean(),
+  mode: z.enum(["suppress", "digest"]),
+  periods: z.array(periodSchema),
+});
+
+type QuietHoursFormValues = z.infer<typeof formSchema>;
+
+const NEW_PERIOD: QuietPeriod = {
+  days: ["monday", "tuesday", "wednesday", "thursday", "friday"],
+  start: "22:00",
+  end: "07:00",
+};
+
+export const QuietHours = () => {
+  const { data: quietHours } = useQuietHours();
+  const saveQuietHours = useSaveQuietHours();
+
+  const form = useForm<QuietHoursFormValues>({
+    resolver: zodResolver(formSchema),
+    defaultValues: { enabled: false, mode: "suppress", periods: [] },
+  });
+
+  const { fields, append, remove } = useFieldArray({
+    control: form.control,
+    name: "periods",
+  });
+
+  useEffect(() => {
+    if (quietHours) {
+      form.reset({
+        enabled: quietHours.enabled ?? false,
+        mode: quietHours.mode ?? "suppress",
+        periods: quietHours.periods ?? [],
+      });
+    }
+  }, [quietHours, form]);
+
+  const onSubmit = async (data: QuietHoursFormValues) => {
+    await saveQuietHours.mutateAsync(data).catch(() => {
+      // Reported by the mutation
+    });
+  };
+
+  return (
+    <form
+      className="space-y-4"
+      id="form-quiet-hours"
+      onSubmit={form.handleSubmit(onSubmit)}
+    >
+      <FieldSet>
+        <FieldLegend className="font-semibold">Quiet Hours</FieldLegend>
+        <div className="flex items-end space-x-4">
+          <Controller
+            control={form.control}
+            name="enabled"
+            render={({ field }) => (
+              <Field className="w-auto">
+                <div className="flex h-9 items-center space-x-2">
+                  <Switch
+                    checked={Boolean(field.value)}
+                    id="form-quiet-hours-enabled"
+                    onCheckedChange={field.onChange}
+                    value={field.value ? "true" : "false"}
+                  />
+                  <FieldLabel
+                    className="font-semibold"
+                    htmlFor="form-quiet-hours-enabled"
+                  >
+                    Enabled
+                  </FieldLabel>
+                </div>
+              </Field>
+            )}
+          />
+          <Controller
+            control={form.control}
+            name="mode"
+            render={({ field }) => (
+              <Field>
+                <FieldLabel
+                  className="font-semibold"
+                  htmlFor="form-quiet-hours-mode"
+                >
+                  Notifications During Quiet Hours
+                </FieldLabel>
+                <Select onValueChange={field.onChange} value={field.value}>
+                  <SelectTrigger id="form-quiet-hours-mode">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="suppress">Drop</SelectItem>
+                    <SelectItem value="digest">
+                      Send a digest when they end
+                    </SelectItem>
+                  </SelectContent>
+                </Select>
+              </Field>
+            )}
+          />
+        </div>
+        <p className="text-muted-foreground text-sm">
+          Times are local, a period ending before it starts runs past
+          midnight. Notifications held for the digest are lost when the app
+          quits.
+        </p>
+        {fields.map((field, index) => (
+          <QuietPeriodFields
+            control={form.control}
+            index={index}
+            key={field.id}
+            onRemove={() => remove(index)}
+          />
+        ))}
+        <Button
+          onClick={() => append(NEW_PERIOD)}
+          type="button"
+          variant="outline"
+        >
+          <PlusIcon className="size-4" />
+          Add Period
+        </Button>
+      </FieldSet>
+      <Field className="justify-end" orientation="horizontal">
+        <Button
+          className="flex-1"
+          disabled={form.formState.isSubmitting}
+          form="form-quiet-hours"
+          type="submit"
+        >
+          {form.formState.isSubmitting && (
+            <Spinner className="size-4 animate-spin" />
+          )}
+          Save Quiet Hours
+        </Button>
+      </Field>
+    </form>
+  );
+};
+
+const QuietPeriodFields = ({
+  control,
+  index,
+  onRemove,
+}: {
+  control: Control<QuietHoursFormValues>;
+  index: number;
+  onRemove: () => void;
+}) => {
+  const id = `form-quiet-hours-periods-${index}`;
+  return (
+    <FieldGroup className="gap-4 rounded-md border p-4">
+      <div className="flex items-start space-x-2">
+        <Controller
+          control={control}
+          name={`periods.${index}.days`}
+          render={({ field, fieldState }) => (
+            <Field className="mr-auto" data-invalid={fieldState.invalid}>
+              <FieldLabel className="font-semibold">Days</FieldLabel>
+              <ToggleGroup
+                onValueChange={field.onChange}
+                size="sm"
+                type="multiple"
+                value={field.value}
+                variant="outline"
+              >
+                {WEEKDAYS.map((day) => (
+                  <ToggleGroupItem className="capitalize" key={day} value={day}>
+                    {day.slice(0, 3)}
+                  </ToggleGroupItem>
+                ))}
+              </ToggleGroup>
+              {fieldState.invalid && <FieldError errors={[fieldState.error]} />}
+            </Field>
+          )}
+        />
+        <Button
+          onClick={onRemove}
+          size="icon"
+          title="Remove period"
+          type="button"
+          variant="ghost"
+        >
+          <Trash2Icon className="size-4" />
+        </Button>
+      </div>
+      <div className="flex space-x-4">
+        <Controller
+          control={control}
+          name={`periods.${index}.start`}
+          render={({ field, fieldState }) => (
+            <Field data-invalid={fieldState.invalid}>
+              <FieldLabel className="font-semibold" htmlFor={`${id}-start`}>
+                Start
+              </FieldLabel>
+              <Input
+                {...field}
+                aria-invalid={fieldState.invalid}
+                id={`${id}-start`}
+                type="time"
+              />
+              {fieldState.invalid && <FieldError errors={[fieldState.error]} />}
+            </Field>
+          )}
+        />
+        <Controller
+          control={control}
+          name={`periods.${index}.end`}
+          render={({ field, fieldState }) => (
+            <Field data-invalid={fieldState.invalid}>
+              <FieldLabel className="font-semibold" htmlFor={`${id}-end`}>
+                End
+              </FieldLabel>
+              <Input
+                {...field}
+                aria-invalid={fieldState.invalid}
+                id={`${id}-end`}
+                type="time"
+              />
+              {fieldState.invalid && <FieldError errors={[fieldState.error]} />}
+            </Field>
+          )}
+        />
+      </div>
+    </FieldGroup>
+  );
+};
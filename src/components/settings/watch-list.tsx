@@ -0,0 +1,257 @@
+import { EyeOffIcon, PlusIcon } from "lucide-react";
+import { useState } from "react";
+import { Badge } from "@/components/ui/badge";
+import { Button } from "@/components/ui/button";
+import {
+  Field,
+  FieldError,
+  FieldLabel,
+  FieldLegend,
+  FieldSet,
+} from "@/components/ui/field";
+import { Input } from "@/components/ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import { Switch } from "@/components/ui/switch";
+import type {
+  ConfigurationStatus,
+  WatchedConfiguration,
+} from "@/lib/bindings";
+import { usePreferences } from "@/services/preferences";
+import {
+  useAddWatchedConfiguration,
+  useConfigurationStatuses,
+  useRemoveWatchedConfiguration,
+  useSetWatchListOptions,
+} from "@/services/watch-list";
+
+type WatchedKind = "glob" | "regex" | "id";
+
+// Name of the server of the settings saved before several servers were
+// supported
+const DEFAULT_SERVER_NAME = "default";
+
+const toWatchedConfiguration = (
+  kind: WatchedKind,
+  value: string,
+  server: string
+): WatchedConfiguration => {
+  switch (kind) {
+    case "id":
+      return { id: value, server };
+    case "regex":
+      return { path: { regex: value } };
+    default:
+      return { path: { glob: value } };
+  }
+};
+
+const describe = (configuration: WatchedConfiguration) => {
+  if ("id" in configuration) {
+    return configuration.server
+      ? `Configuration #${configuration.id} of ${configuration.server}`
+      : `Configuration #${configuration.id}`;
+  }
+  return "regex" in configuration.path
+    ? `${configuration.path.regex} (regex)`
+    : configuration.path.glob;
+};
+
+const statusesOf = (
+  configuration: WatchedConfiguration,
+  statuses: ConfigurationStatus[]
+) =>
+  "id" in configuration
+    ? statuses.filter(
+        (status) =>
+          status.configuration === configuration.id &&
+          (!configuration.server || status.server === configuration.server)
+      )
+    : [];
+
+export const WatchList = () => {
+  const { data: preferences } = usePreferences();
+  const { data: statuses = [] } = useConfigurationStatuses();
+  const addConfiguration = useAddWatchedConfiguration();
+  const removeConfiguration = useRemoveWatchedConfiguration();
+  const setOptions = useSetWatchListOptions();
+  const [kind, setKind] = useState<WatchedKind>("glob");
+  const [value, setValue] = useState("");
+  const [server, setServer] = useState<string>();
+  const [error, setError] = useState<string>();
+
+  const watchList = preferences?.watch_list;
+  const configurations = watchList?.configurations ?? [];
+  const notifyOnlyWatched = watchList?.notify_only_watched ?? false;
+  const statusOnlyWatched = watchList?.status_only_watched ?? false;
+  const servers = preferences?.servers?.length
+    ? preferences.servers.map((server) => server.name)
+    : [DEFAULT_SERVER_NAME];
+  const selectedServer =
+    server && servers.includes(server) ? server : servers[0];
+
+  const onAdd = () => {
+    const trimmed = value.trim();
+    if (!trimmed) {
+      setError("Enter a configuration path or id");
+      return;
+    }
+    if (kind === "id" && !/^\d+$/.test(trimmed)) {
+      setError("Configuration id must be a number");
+      return;
+    }
+    setError(undefined);
+    const configuration = toWatchedConfiguration(kind, trimmed, selectedServer);
+    addConfiguration.mutate(configuration, {
+      onSuccess: () => setValue(""),
+    });
+  };
+
+  return (
+    <FieldSet>
+      <FieldLegend className="font-semibold">Watch List</FieldLegend>
+      <div className="flex items-center space-x-4">
+        <Field className="w-auto">
+          <div className="flex items-center space-x-2">
+            <Switch
+              checked={notifyOnlyWatched}
+              id="form-watch-list-notify-only-watched"
+              onCheckedChange={(checked) =>
+                setOptions.mutate({
+                  notifyOnlyWatched: checked,
+                  statusOnlyWatched,
+                })
+              }
+            />
+            <FieldLabel
+              className="font-semibold"
+              htmlFor="form-watch-list-notify-only-watched"
+            >
+              Only Notify Watched
+            </FieldLabel>
+          </div>
+        </Field>
+        <Field className="w-auto">
+          <div className="flex items-center space-x-2">
+            <Switch
+              checked={statusOnlyWatched}
+              id="form-watch-list-status-only-watched"
+              onCheckedChange={(checked) =>
+                setOptions.mutate({
+                  notifyOnlyWatched,
+                  statusOnlyWatched: checked,
+                })
+              }
+            />
+            <FieldLabel
+              className="font-semibold"
+              htmlFor="form-watch-list-status-only-watched"
+            >
+              Tray Status From Watched Only
+            </FieldLabel>
+          </div>
+        </Field>
+      </div>
+      {configurations.length === 0 ? (
+        <p className="text-muted-foreground text-sm">
+          No configuration is watched, all of them are notified.
+        </p>
+      ) : (
+        <ul className="divide-y rounded-md border">
+          {configurations.map((configuration) => (
+            <li
+              className="flex items-center space-x-2 px-4 py-2"
+              key={JSON.stringify(configuration)}
+            >
+              <span className="mr-auto truncate">
+                {describe(configuration)}
+              </span>
+              {statusesOf(configuration, statuses).map((status) => (
+                <Badge
+                  key={status.server}
+                  title={status.error ?? status.configurationPath}
+                  variant="outline"
+                >
+                  {status.server}:{" "}
+                  {status.error
+                    ? "error"
+                    : (status.latestBuild?.status.toLowerCase() ?? "not built")}
+                </Badge>
+              ))}
+              <Button
+                disabled={removeConfiguration.isPending}
+                onClick={() => removeConfiguration.mutate(configuration)}
+                size="icon"
+                title="Stop watching"
+                type="button"
+                variant="ghost"
+              >
+                <EyeOffIcon className="size-4" />
+              </Button>
+            </li>
+          ))}
+        </ul>
+      )}
+      <Field data-invalid={Boolean(error)}>
+        <div className="flex space-x-2">
+          <Select
+            onValueChange={(kind) => setKind(kind as WatchedKind)}
+            value={kind}
+          >
+            <SelectTrigger className="w-32" id="form-watch-list-kind">
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="glob">Glob</SelectItem>
+              <SelectItem value="regex">Regex</SelectItem>
+              <SelectItem value="id">Id</SelectItem>
+            </SelectContent>
+          </Select>
+          {kind === "id" && (
+            <Select onValueChange={setServer} value={selectedServer}>
+              <SelectTrigger className="w-40" id="form-watch-list-server">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                {servers.map((name) => (
+                  <SelectItem key={name} value={name}>
+                    {name}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+          )}
+          <Input
+            aria-invalid={Boolean(error)}
+            autoComplete="off"
+            id="form-watch-list-value"
+            onChange={(event) => setValue(event.target.value)}
+            onKeyDown={(event) => {
+              if (event.key === "Enter") {
+                event.preventDefault();
+                onAdd();
+              }
+            }}
+            placeholder={kind === "id" ? "42" : "root/releases/**"}
+            value={value}
+          />
+          <Button
+            disabled={addConfiguration.isPending}
+            onClick={onAdd}
+            type="button"
+            variant="outline"
+          >
+            <PlusIcon className="size-4" />
+            Watch
+          </Button>
+        </div>
+        {error && <FieldError>{error}</FieldError>}
+      </Field>
+    </FieldSet>
+  );
+};
@@ -1,8 +1,14 @@
+import { useQueryClient } from "@tanstack/react-query";
 import { listen } from "@tauri-apps/api/event";
 import { useEffect } from "react";
 import { useNavigate } from "react-router-dom";
 import { logger } from "@/lib/logger";
+import { setupNotificationActions } from "@/lib/notifications";
 import { runUpdateFlow } from "@/lib/updater";
+import { preferencesQueryKeys } from "@/services/preferences";
+import { quietHoursQueryKeys } from "@/services/quiet-hours";
+import { rulesQueryKeys } from "@/services/rules";
+import { watchListQueryKeys } from "@/services/watch-list";
 import { useCommandContext } from "./use-command-context";
 
 /**
@@ -14,6 +20,7 @@ import { useCommandContext } from "./use-command-context";
 export function useMainWindowEventListeners() {
   const commandContext = useCommandContext();
   const navigate = useNavigate();
+  const queryClient = useQueryClient();
 
   useEffect(() => {
     // Set up native menu event listeners
@@ -70,6 +77,46 @@ export function useMainWindowEventListeners() {
           }
         }),
 
+        listen<boolean>("polling-paused-changed", (event) => {
+          logger.info("Polling paused changed event received", {
+            paused: event.payload,
+          });
+          queryClient.invalidateQueries({
+            queryKey: preferencesQueryKeys.preferences(),
+          });
+        }),
+
+        listen("notification-rules-changed", () => {
+          logger.info("Notification rules changed event received");
+          queryClient.invalidateQueries({
+            queryKey: rulesQueryKeys.rules(),
+          });
+        }),
+
+        listen("quiet-hours-changed", () => {
+          logger.info("Quiet hours changed event received");
+          queryClient.invalidateQueries({
+            queryKey: quietHoursQueryKeys.quietHours(),
+          });
+        }),
+
+        listen("watch-list-changed", () => {
+          logger.info("Watch list changed event received");
+          queryClient.invalidateQueries({
+            queryKey: preferencesQueryKeys.preferences(),
+          });
+          queryClient.invalidateQueries({
+            queryKey: watchListQueryKeys.statuses(),
+          });
+        }),
+
+        listen("configuration-statuses-changed", () => {
+          logger.debug("Configuration statuses changed event received");
+          queryClient.invalidateQueries({
+            queryKey: watchListQueryKeys.statuses(),
+          });
+        }),
+
         listen("menu-preferences", () => {
           logger.debug("Preferences menu event received");
           commandContext.openPreferences();
@@ -99,7 +146,18 @@ export function useMainWindowEventListeners() {
         }
       }
     };
-  }, [commandContext, navigate]);
+  }, [commandContext, navigate, queryClient]);
+
+  useEffect(() => {
+    const listener = setupNotificationActions().catch((error) => {
+      logger.error("Failed to setup notification actions:", error);
+      return null;
+    });
+
+    return () => {
+      listener.then((l) => l?.unregister());
+    };
+  }, []);
 
   // Future: Other global event listeners can be added here
   // useWindowFocusListeners()
@@ -8,12 +8,33 @@
 
 
 export const commands = {
+/**
+ * Settings of the app, with the access tokens redacted
+ */
 async loadSettings() : Promise<AppSettings> {
     return await TAURI_INVOKE("load_settings");
 },
+/**
+ * Saves the settings, the servers saved are kept when the settings have none, as sent by the
+ * forms not editing them. The settings edited by their own commands are kept as saved.
+ */
 async saveSettings(settings: AppSettings) : Promise<null> {
     return await TAURI_INVOKE("save_settings", { settings });
 },
+/**
+ * Checks the server URL, the credentials and the version of the server before the settings
+ * are saved. A redacted token stands for the saved token of the server.
+ */
+async testConnection(server: ServerProfile) : Promise<ConnectionDiagnostic> {
+    return await TAURI_INVOKE("test_connection", { server });
+},
+/**
+ * Handles the actions performed on the notifications, forwarded by the frontend on mobile
+ * platforms and by the desktop notifications otherwise
+ */
+async handleNotificationAction(action: NotificationAction, target: NotificationTarget) : Promise<null> {
+    return await TAURI_INVOKE("handle_notification_action", { action, target });
+},
 async getConfigDir() : Promise<string> {
     return await TAURI_INVOKE("get_config_dir");
 },
@@ -23,6 +44,12 @@ async getLogsDir() : Promise<string> {
 async showDashboardWindow() : Promise<null> {
     return await TAURI_INVOKE("show_dashboard_window");
 },
+/**
+ * Opens the given build of the QuickBuild server in the dashboard window
+ */
+async showBuildInDashboard(server: string, buildId: string) : Promise<null> {
+    return await TAURI_INVOKE("show_build_in_dashboard", { server, buildId });
+},
 async closeDashboardWindow() : Promise<null> {
     return await TAURI_INVOKE("close_dashboard_window");
 },
@@ -38,8 +65,126 @@ async getAppInfo() : Promise<[string, string]> {
 async getBuilds() : Promise<GetBuildsResponse> {
     return await TAURI_INVOKE("get_builds");
 },
+/**
+ * Searches the build history with filters and pagination
+ */
+async queryBuilds(query: BuildQuery) : Promise<QueryBuildsResponse> {
+    return await TAURI_INVOKE("query_builds", { query });
+},
+/**
+ * Marks a build of the history as read or unread
+ */
+async markBuildRead(server: string, buildId: string, read: boolean) : Promise<null> {
+    return await TAURI_INVOKE("mark_build_read", { server, buildId, read });
+},
+/**
+ * Marks every build of the history as read, returns how many builds were unread
+ */
+async markAllBuildsRead() : Promise<number> {
+    return await TAURI_INVOKE("mark_all_builds_read");
+},
+/**
+ * Requests a new build of the configuration, returns the id of the build request
+ */
+async requestBuild(server: string, configurationId: string, variables: Partial<{ [key in string]: string }> | null) : Promise<string> {
+    return await TAURI_INVOKE("request_build", { server, configurationId, variables });
+},
+/**
+ * Requests a new build of the configuration of the given build, with the variable values the
+ * build was requested with, returns the id of the build request
+ */
+async rebuild(server: string, buildId: string, configurationId: string) : Promise<string> {
+    return await TAURI_INVOKE("rebuild", { server, buildId, configurationId });
+},
+/**
+ * Stops a running build, or removes it from the queue when it is still waiting
+ */
+async cancelBuild(server: string, buildId: string) : Promise<null> {
+    return await TAURI_INVOKE("cancel_build", { server, buildId });
+},
 async getAlerts() : Promise<GetAlertsResponse> {
     return await TAURI_INVOKE("get_alerts");
+},
+/**
+ * Acknowledges the alert on the QuickBuild server, then in the local alert history
+ */
+async acknowledgeAlert(server: string, alertId: string) : Promise<null> {
+    return await TAURI_INVOKE("acknowledge_alert", { server, alertId });
+},
+/**
+ * Acknowledges every alert of the local history not acknowledged yet, returns how many
+ * alerts were acknowledged along with the errors of the alerts that failed
+ */
+async acknowledgeAllAlerts() : Promise<AcknowledgeAlertsResult> {
+    return await TAURI_INVOKE("acknowledge_all_alerts");
+},
+/**
+ * Marks an alert of the history as read or unread
+ */
+async markAlertRead(server: string, alertId: string, read: boolean) : Promise<null> {
+    return await TAURI_INVOKE("mark_alert_read", { server, alertId, read });
+},
+/**
+ * Marks every alert of the history as read, returns how many alerts were unread
+ */
+async markAllAlertsRead() : Promise<number> {
+    return await TAURI_INVOKE("mark_all_alerts_read");
+},
+/**
+ * Notification rules of the settings, in the order they are evaluated
+ */
+async getNotificationRules() : Promise<NotificationRule[]> {
+    return await TAURI_INVOKE("get_notification_rules");
+},
+/**
+ * Replaces the notification rules, nothing is saved when a rule is invalid
+ */
+async saveNotificationRules(rules: NotificationRule[]) : Promise<null> {
+    return await TAURI_INVOKE("save_notification_rules", { rules });
+},
+/**
+ * Quiet hours of the settings
+ */
+async getQuietHours() : Promise<QuietHours> {
+    return await TAURI_INVOKE("get_quiet_hours");
+},
+/**
+ * Replaces the quiet hours, nothing is saved when a period has an invalid time. The
+ * notifications held back for the digest are only kept in memory and lost when the app quits.
+ */
+async saveQuietHours(quietHours: QuietHours) : Promise<null> {
+    return await TAURI_INVOKE("save_quiet_hours", { quietHours });
+},
+/**
+ * Adds a configuration to the watch list, returns whether it was not watched yet
+ */
+async addWatchedConfiguration(configuration: WatchedConfiguration) : Promise<boolean> {
+    return await TAURI_INVOKE("add_watched_configuration", { configuration });
+},
+/**
+ * Removes a configuration from the watch list, returns whether it was watched
+ */
+async removeWatchedConfiguration(configuration: WatchedConfiguration) : Promise<boolean> {
+    return await TAURI_INVOKE("remove_watched_configuration", { configuration });
+},
+/**
+ * Sets whether only the watched configurations are notified and decide the tray status
+ */
+async setWatchListOptions(notifyOnlyWatched: boolean, statusOnlyWatched: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_watch_list_options", { notifyOnlyWatched, statusOnlyWatched });
+},
+/**
+ * Current status of the watched configurations, from their latest build on the server
+ */
+async getConfigurationStatuses() : Promise<ConfigurationStatus[]> {
+    return await TAURI_INVOKE("get_configuration_statuses");
+},
+/**
+ * Polls the servers right away instead of waiting for the next poll, returns their status
+ * once they are polled
+ */
+async pollNow() : Promise<ServerStatus[]> {
+    return await TAURI_INVOKE("poll_now");
 }
 }
 
@@ -53,15 +198,77 @@ async getAlerts() : Promise<GetAlertsResponse> {
 
 /** user-defined types **/
 
-export type Alert = { id: string; subject: string; priority: AlertPriority; category: AlertCategory; alertMessage: string; trigger: string; fixed: boolean; ctime: string; ackTime: string }
+export type AcknowledgeAlertsResult = { acknowledged: number; errors: string[] }
+export type Alert = { id: string; server: string; subject: string; priority: AlertPriority; category: AlertCategory; alertMessage: string; trigger: string; fixed: boolean; ctime: string; ackTime: string; read?: boolean }
 export type AlertCategory = "SYSTEM" | "METRIC"
 export type AlertPriority = "LOW" | "MEDIUM" | "HIGH"
-export type AppSettings = { enable_notifications?: boolean; notifications_total?: number; theme?: AppTheme; server_url?: string; user?: string; token?: string; poll_interval_in_secs?: number; paused?: boolean }
+export type AlertRuleFilter = { priorities?: AlertPriority[]; categories?: AlertCategory[] }
+export type AppSettings = { enable_notifications?: boolean; notifications_total?: number; history_max_age_days?: number; theme?: AppTheme; server_url?: string; user?: string; token?: string; poll_interval_in_secs?: number; paused?: boolean; paused_until?: string | null; notification_rules?: NotificationRule[]; quiet_hours?: QuietHours; digest_window_in_mins?: number; watch_list?: WatchList; servers?: ServerProfile[] }
 export type AppTheme = "system" | "light" | "dark"
-export type Build = { id: string; configuration: string; configurationPath?: string; masterNodeAddress: string; requester: string; requesterName?: string | null; canceller?: string | null; cancellerName?: string | null; version: string; status: BuildStatus; beginDate: string; statusDate?: string | null; duration: string; waitDuration: string }
+export type Build = { id: string; server: string; configuration: string; configurationPath?: string; masterNodeAddress: string; requester: string; requesterName?: string | null; canceller?: string | null; cancellerName?: string | null; version: string; status: BuildStatus; beginDate: string; statusDate?: string | null; duration: string; waitDuration: string; read?: boolean }
+export type BuildQuery = { server?: string | null; statuses?: BuildStatus[]; configurationPath?: string | null; requester?: string | null; version?: string | null; beginDateFrom?: string | null; beginDateTo?: string | null; sortOrder?: SortOrder; offset?: number; limit?: number }
+export type BuildRuleFilter = { configuration_path?: PathPattern | null; statuses?: BuildStatus[]; requester?: string | null; only_mine?: boolean }
+export type BuildTransition = { kind: TransitionKind; build: Build; failureCount: number; failingDuration: string | null }
 export type BuildStatus = "SUCCESSFUL" | "RECOMMENDED" | "FAILED" | "CANCELLED" | "TIMEOUT" | "RUNNING"
-export type GetAlertsResponse = { alerts: Alert[]; error: string | null; lastPollingTime?: string | null }
-export type GetBuildsResponse = { builds: Build[]; error: string | null; lastPollingTime?: string | null }
+/**
+ * Outcome of a check of the connection test
+ */
+export type CheckStatus = "passed" | "failed" | "skipped"
+/**
+ * Current status of a watched configuration, told by its latest build on the server
+ */
+export type ConfigurationStatus = { server: string; configuration: string; configurationPath: string; latestBuild: Build | null; checkedTime: string; error: string | null }
+/**
+ * Single check of the connection test, with the error when it failed
+ */
+export type ConnectionCheck = { status: CheckStatus; error: string | null; errorKind: QuickBuildErrorKind | null }
+/**
+ * Diagnostic of the connection to a QuickBuild server, run from the settings before they
+ * are saved
+ */
+export type ConnectionDiagnostic = { reachable: ConnectionCheck; authenticated: ConnectionCheck; notificationsEndpoint: ConnectionCheck; version: ConnectionCheck; serverVersion: string | null; ok: boolean }
+export type GetAlertsResponse = { alerts: Alert[]; error: string | null; errorKind: QuickBuildErrorKind | null; lastPollingTime?: string | null; unreadCount: number; servers: ServerStatus[] }
+export type GetBuildsResponse = { builds: Build[]; error: string | null; errorKind: QuickBuildErrorKind | null; lastPollingTime?: string | null; consecutiveFailures: number; nextRetryTime?: string | null; unreadCount: number; servers: ServerStatus[] }
+/**
+ * Action performed by the user on a notification
+ */
+export type NotificationAction = "open" | "open_build" | "rebuild" | "acknowledge"
+export type NotificationRule = { name?: string; enabled?: boolean; filter: RuleFilter; action?: RuleAction }
+/**
+ * Build or alert a notification is about, stored in the extra payload of the notification. Ids
+ * are strings as in the bindings, numbers are still read from the notifications sent before.
+ */
+export type NotificationTarget = { server?: string | null; buildId?: string | null; configurationId?: string | null; alertId?: string | null }
+export type PathPattern = { glob: string } | { regex: string }
+export type QueryBuildsResponse = { builds: Build[]; total: number }
+export type QuietHours = { enabled?: boolean; mode?: QuietHoursMode; periods?: QuietPeriod[] }
+export type QuietHoursMode = "suppress" | "digest"
+export type QuietPeriod = { days: Weekday[]; start: string; end: string }
+export type QuickBuildErrorKind = "connect" | "tls" | "auth" | "not_found" | "server" | "timeout" | "decode" | "other"
+export type RuleAction = "notify" | "silent" | "ignore"
+export type RuleFilter = { build: BuildRuleFilter } | { alert: AlertRuleFilter }
+/**
+ * QuickBuild server polled by the tray monitor, with its own credentials
+ */
+export type ServerProfile = { name: string; server_url?: string; user?: string; token?: string; enabled?: boolean }
+/**
+ * Polling status of a server, reported along with the build and alert history
+ */
+export type ServerStatus = { name: string; error: string | null; errorKind: QuickBuildErrorKind | null; lastPollingTime?: string | null; consecutiveFailures: number; nextRetryTime?: string | null }
+export type SortOrder = "newest_first" | "oldest_first"
+export type TransitionKind = "broke" | "fixed"
+/**
+ * Configurations the user cares about. Builds of other configurations are still recorded in
+ * the history, and the restrictions only apply once the list has configurations.
+ */
+export type WatchList = { configurations?: WatchedConfiguration[]; notify_only_watched?: boolean; status_only_watched?: boolean }
+/**
+ * Configuration of the watch list, matched by its id or its path. Ids are only unique on
+ * their server, an id without server matches the configurations of every server, as saved
+ * before servers were named.
+ */
+export type WatchedConfiguration = { id: string; server?: string | null } | { path: PathPattern }
+export type Weekday = "monday" | "tuesday" | "wednesday" | "thursday" | "friday" | "saturday" | "sunday"
 
 /** tauri-specta globals **/
 
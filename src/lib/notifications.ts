@@ -3,7 +3,17 @@
  */
 
 import { invoke } from "@tauri-apps/api/core";
+import {
+  onAction,
+  type PluginListener,
+  registerActionTypes,
+} from "@tauri-apps/plugin-notification";
 import { toast } from "sonner";
+import {
+  commands,
+  type NotificationAction,
+  type NotificationTarget,
+} from "./bindings";
 import { logger } from "./logger";
 
 type NotificationType = "success" | "error" | "info" | "warning";
@@ -107,3 +117,52 @@ export const notifications = {
 
 // Export individual convenience functions
 export const { success, error, info, warning } = notifications;
+
+/** Action ids of the notification buttons, matching NotificationAction on the Rust side */
+const NOTIFICATION_ACTIONS: NotificationAction[] = [
+  "open_build",
+  "rebuild",
+  "acknowledge",
+];
+
+/**
+ * Register the "Open build", "Rebuild" and "Acknowledge" buttons of build and alert
+ * notifications, and forward the actions performed on them to the Rust handler
+ *
+ * Only mobile platforms support notification actions here. Registering them
+ * fails on desktop, where the backend shows build and alert notifications with
+ * their actions and handles them itself, so it is not an error.
+ */
+export async function setupNotificationActions(): Promise<PluginListener | null> {
+  try {
+    await registerActionTypes([
+      {
+        id: "build",
+        actions: [
+          { id: "open_build", title: "Open build", foreground: true },
+          { id: "rebuild", title: "Rebuild" },
+        ],
+      },
+      {
+        id: "alert",
+        actions: [{ id: "acknowledge", title: "Acknowledge" }],
+      },
+    ]);
+  } catch (error) {
+    logger.debug("Notification actions are not supported", { error });
+    return null;
+  }
+
+  return await onAction(async (notification) => {
+    const actionId = (notification as { actionId?: string }).actionId;
+    const action = NOTIFICATION_ACTIONS.find((a) => a === actionId) ?? "open";
+    const target = (notification.extra ?? {}) as NotificationTarget;
+    logger.info("Notification action performed", { action, target });
+    try {
+      await commands.handleNotificationAction(action, target);
+    } catch (error) {
+      logger.error("Failed to handle notification action", { error });
+      toast.error(`Failed to ${action.replace("_", " ")}: ${error}`);
+    }
+  });
+}
@@ -1,6 +1,6 @@
-import { useQuery } from "@tanstack/react-query";
+import { useMutation, useQuery } from "@tanstack/react-query";
 import { invoke } from "@tauri-apps/api/core";
-import type { GetAlertsResponse } from "@/lib/bindings";
+import { commands, type GetAlertsResponse } from "@/lib/bindings";
 import { logger } from "@/lib/logger";
 
 // Query keys for preferences
@@ -30,3 +30,53 @@ export function useAlerts() {
     gcTime: 1000 * 60 * 10, // 10 minutes
   });
 }
+
+export function useAcknowledgeAlert() {
+  return useMutation({
+    mutationFn: async ({
+      server,
+      alertId,
+    }: {
+      server: string;
+      alertId: string;
+    }) => {
+      logger.info("Acknowledging alert", { server, alertId });
+      return await commands.acknowledgeAlert(server, alertId);
+    },
+    onError: (error) => {
+      logger.error("Failed to acknowledge alert", { error });
+    },
+  });
+}
+
+export function useMarkAlertRead() {
+  return useMutation({
+    mutationFn: async ({
+      server,
+      alertId,
+      read,
+    }: {
+      server: string;
+      alertId: string;
+      read: boolean;
+    }) => {
+      logger.debug("Marking alert as read", { server, alertId, read });
+      return await commands.markAlertRead(server, alertId, read);
+    },
+    onError: (error) => {
+      logger.error("Failed to mark alert as read", { error });
+    },
+  });
+}
+
+export function useMarkAllAlertsRead() {
+  return useMutation({
+    mutationFn: async () => {
+      logger.info("Marking all alerts as read");
+      return await commands.markAllAlertsRead();
+    },
+    onError: (error) => {
+      logger.error("Failed to mark alerts as read", { error });
+    },
+  });
+}
@@ -1,6 +1,6 @@
-import { useQuery } from "@tanstack/react-query";
+import { useMutation, useQuery } from "@tanstack/react-query";
 import { invoke } from "@tauri-apps/api/core";
-import type { GetBuildsResponse } from "@/lib/bindings";
+import { commands, type GetBuildsResponse } from "@/lib/bindings";
 import { logger } from "@/lib/logger";
 
 // Query keys for preferences
@@ -30,3 +30,85 @@ export function useBuilds() {
     gcTime: 1000 * 60 * 10, // 10 minutes
   });
 }
+
+export function useRebuild() {
+  return useMutation({
+    mutationFn: async ({
+      server,
+      buildId,
+      configurationId,
+    }: {
+      server: string;
+      buildId: string;
+      configurationId: string;
+    }) => {
+      logger.info("Rebuilding build", { server, buildId });
+      return await commands.rebuild(server, buildId, configurationId);
+    },
+    onError: (error) => {
+      logger.error("Failed to rebuild", { error });
+    },
+  });
+}
+
+export function useCancelBuild() {
+  return useMutation({
+    mutationFn: async ({
+      server,
+      buildId,
+    }: {
+      server: string;
+      buildId: string;
+    }) => {
+      logger.info("Cancelling build", { server, buildId });
+      return await commands.cancelBuild(server, buildId);
+    },
+    onError: (error) => {
+      logger.error("Failed to cancel build", { error });
+    },
+  });
+}
+
+export function useMarkBuildRead() {
+  return useMutation({
+    mutationFn: async ({
+      server,
+      buildId,
+      read,
+    }: {
+      server: string;
+      buildId: string;
+      read: boolean;
+    }) => {
+      logger.debug("Marking build as read", { server, buildId, read });
+      return await commands.markBuildRead(server, buildId, read);
+    },
+    onError: (error) => {
+      logger.error("Failed to mark build as read", { error });
+    },
+  });
+}
+
+export function useMarkAllBuildsRead() {
+  return useMutation({
+    mutationFn: async () => {
+      logger.info("Marking all builds as read");
+      return await commands.markAllBuildsRead();
+    },
+    onError: (error) => {
+      logger.error("Failed to mark builds as read", { error });
+    },
+  });
+}
+
+export function usePollNow() {
+  return useMutation({
+    mutationFn: async () => {
+      logger.info("Polling servers now");
+      return await commands.pollNow();
+    },
+    onError: (error) => {
+      logger.error("Failed to poll servers", { error });
+    },
+  });
+}
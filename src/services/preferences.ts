@@ -1,8 +1,9 @@
 import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
 import { invoke } from "@tauri-apps/api/core";
 import { toast } from "sonner";
+import { commands, type ServerProfile } from "@/lib/bindings";
 import { logger } from "@/lib/logger";
-import type { AppPreferences } from "@/types/preferences";
+import { type AppPreferences, REDACTED_TOKEN } from "@/types/preferences";
 
 // Query keys for preferences
 export const preferencesQueryKeys = {
@@ -27,6 +28,8 @@ export function usePreferences() {
           theme: "system",
           enable_notifications: true,
           notifications_total: 100,
+          history_max_age_days: 0,
+          digest_window_in_mins: 0,
           server_url: "http://quickbuild:8810",
           user: "user",
           token: "token",
@@ -44,14 +47,26 @@ export function useSavePreferences() {
 
   return useMutation({
     mutationFn: async (preferences: AppPreferences) => {
+      // typed tokens are never logged
+      const logged = {
+        ...preferences,
+        token: REDACTED_TOKEN,
+        servers: preferences.servers?.map((server) => ({
+          ...server,
+          token: REDACTED_TOKEN,
+        })),
+      };
       try {
-        logger.debug("Saving preferences to backend", { preferences });
+        logger.debug("Saving preferences to backend", { preferences: logged });
         await invoke("save_settings", { settings: preferences });
         logger.info("Preferences saved successfully");
       } catch (error) {
         const message =
           error instanceof Error ? error.message : (error as string);
-        logger.error("Failed to save preferences", { error, preferences });
+        logger.error("Failed to save preferences", {
+          error,
+          preferences: logged,
+        });
         toast.error("Failed to save preferences", { description: message });
         throw error;
       }
@@ -64,3 +79,21 @@ export function useSavePreferences() {
     },
   });
 }
+
+export function useTestConnection() {
+  return useMutation({
+    mutationFn: async (server: ServerProfile) => {
+      logger.info("Testing connection", {
+        name: server.name,
+        serverUrl: server.server_url,
+        user: server.user,
+      });
+      const diagnostic = await commands.testConnection(server);
+      logger.info("Connection tested", { diagnostic });
+      return diagnostic;
+    },
+    onError: (error) => {
+      logger.error("Failed to test connection", { error });
+    },
+  });
+}
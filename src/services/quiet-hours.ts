@@ -0,0 +1,46 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { toast } from "sonner";
+import { commands, type QuietHours } from "@/lib/bindings";
+import { logger } from "@/lib/logger";
+
+// Query keys for quiet hours
+export const quietHoursQueryKeys = {
+  all: ["quiet-hours"] as const,
+  quietHours: () => [...quietHoursQueryKeys.all] as const,
+};
+
+export function useQuietHours() {
+  return useQuery({
+    queryKey: quietHoursQueryKeys.quietHours(),
+    queryFn: async (): Promise<QuietHours> => {
+      logger.debug("Loading quiet hours from backend");
+      return await commands.getQuietHours();
+    },
+    staleTime: 1000 * 60 * 5, // 5 minutes
+    gcTime: 1000 * 60 * 10, // 10 minutes
+  });
+}
+
+export function useSaveQuietHours() {
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: async (quietHours: QuietHours) => {
+      logger.info("Saving quiet hours", {
+        periods: quietHours.periods?.length ?? 0,
+      });
+      await commands.saveQuietHours(quietHours);
+    },
+    onError: (error) => {
+      const message =
+        error instanceof Error ? error.message : (error as unknown as string);
+      logger.error("Failed to save quiet hours", { error });
+      toast.error("Failed to save quiet hours", {
+        description: message,
+      });
+    },
+    onSuccess: (_, quietHours) => {
+      queryClient.setQueryData(quietHoursQueryKeys.quietHours(), quietHours);
+    },
+  });
+}
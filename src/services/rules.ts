@@ -0,0 +1,44 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { toast } from "sonner";
+import { commands, type NotificationRule } from "@/lib/bindings";
+import { logger } from "@/lib/logger";
+
+// Query keys for notification rules
+export const rulesQueryKeys = {
+  all: ["notification-rules"] as const,
+  rules: () => [...rulesQueryKeys.all] as const,
+};
+
+export function useNotificationRules() {
+  return useQuery({
+    queryKey: rulesQueryKeys.rules(),
+    queryFn: async (): Promise<NotificationRule[]> => {
+      logger.debug("Loading notification rules from backend");
+      return await commands.getNotificationRules();
+    },
+    staleTime: 1000 * 60 * 5, // 5 minutes
+    gcTime: 1000 * 60 * 10, // 10 minutes
+  });
+}
+
+export function useSaveNotificationRules() {
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: async (rules: NotificationRule[]) => {
+      logger.info("Saving notification rules", { count: rules.length });
+      await commands.saveNotificationRules(rules);
+    },
+    onError: (error) => {
+      const message =
+        error instanceof Error ? error.message : (error as unknown as string);
+      logger.error("Failed to save notification rules", { error });
+      toast.error("Failed to save notification rules", {
+        description: message,
+      });
+    },
+    onSuccess: (_, rules) => {
+      queryClient.setQueryData(rulesQueryKeys.rules(), rules);
+    },
+  });
+}
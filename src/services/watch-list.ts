@@ -0,0 +1,72 @@
+import { useMutation, useQuery } from "@tanstack/react-query";
+import { toast } from "sonner";
+import {
+  type ConfigurationStatus,
+  commands,
+  type WatchedConfiguration,
+} from "@/lib/bindings";
+import { logger } from "@/lib/logger";
+
+// Query keys for the watched configurations
+export const watchListQueryKeys = {
+  all: ["watch-list"] as const,
+  statuses: () => [...watchListQueryKeys.all, "statuses"] as const,
+};
+
+const reportError = (title: string) => (error: unknown) => {
+  const message =
+    error instanceof Error ? error.message : (error as unknown as string);
+  logger.error(title, { error });
+  toast.error(title, { description: message });
+};
+
+export function useConfigurationStatuses() {
+  return useQuery({
+    queryKey: watchListQueryKeys.statuses(),
+    queryFn: async (): Promise<ConfigurationStatus[]> => {
+      logger.debug("Loading configuration statuses from backend");
+      return await commands.getConfigurationStatuses();
+    },
+  });
+}
+
+// The preferences holding the watch list are refreshed by the
+// watch-list-changed event
+export function useAddWatchedConfiguration() {
+  return useMutation({
+    mutationFn: async (configuration: WatchedConfiguration) => {
+      logger.info("Adding watched configuration", { configuration });
+      return await commands.addWatchedConfiguration(configuration);
+    },
+    onError: reportError("Failed to watch configuration"),
+  });
+}
+
+export function useRemoveWatchedConfiguration() {
+  return useMutation({
+    mutationFn: async (configuration: WatchedConfiguration) => {
+      logger.info("Removing watched configuration", { configuration });
+      return await commands.removeWatchedConfiguration(configuration);
+    },
+    onError: reportError("Failed to unwatch configuration"),
+  });
+}
+
+export function useSetWatchListOptions() {
+  return useMutation({
+    mutationFn: async ({
+      notifyOnlyWatched,
+      statusOnlyWatched,
+    }: {
+      notifyOnlyWatched: boolean;
+      statusOnlyWatched: boolean;
+    }) => {
+      logger.info("Setting watch list options", {
+        notifyOnlyWatched,
+        statusOnlyWatched,
+      });
+      await commands.setWatchListOptions(notifyOnlyWatched, statusOnlyWatched);
+    },
+    onError: reportError("Failed to save watch list options"),
+  });
+}
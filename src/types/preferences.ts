@@ -4,10 +4,15 @@ import type { AppSettings } from "@/lib/bindings";
 // Use the auto-generated type for consistency with Rust backend
 export type AppPreferences = AppSettings;
 
+// Stands for the saved token in the loaded settings, saving it back keeps the saved token
+export const REDACTED_TOKEN = "********";
+
 export const defaultPreferences: AppPreferences = {
   theme: "system",
   enable_notifications: true,
   notifications_total: 100,
+  history_max_age_days: 0,
+  digest_window_in_mins: 0,
   server_url: "http://quickbuild:8810",
   user: "user",
   token: "token",
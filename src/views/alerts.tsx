@@ -1,6 +1,12 @@
 import { listen } from "@tauri-apps/api/event";
 import { format } from "date-fns";
-import { AlertCircleIcon, RefreshCcwIcon } from "lucide-react";
+import {
+  AlertCircleIcon,
+  CheckCheckIcon,
+  CheckIcon,
+  CircleIcon,
+  RefreshCcwIcon,
+} from "lucide-react";
 import { type ReactNode, useEffect, useRef } from "react";
 import { useLocation } from "react-router-dom";
 import { Loading } from "@/components/loading";
@@ -21,13 +27,20 @@ import type { Alert } from "@/lib/bindings";
 import { logger } from "@/lib/logger";
 import { formatTimeAgo } from "@/lib/time";
 import { cn } from "@/lib/utils";
-import { useAlerts } from "@/services/alerts";
+import {
+  useAcknowledgeAlert,
+  useAlerts,
+  useMarkAlertRead,
+  useMarkAllAlertsRead,
+} from "@/services/alerts";
 
 export const AlertsView = () => {
   const location = useLocation();
   const prevLocationRef = useRef<string | null>(null);
 
   const { data, isLoading, isError, error, refetch } = useAlerts();
+  const markAllAlertsRead = useMarkAllAlertsRead();
+  const unreadCount = data?.unreadCount ?? 0;
 
   // Refetch data when route changes to this page
   useEffect(() => {
@@ -141,7 +154,30 @@ export const AlertsView = () => {
     <Card className="m-0 gap-0 rounded-none border-none py-0">
       <CardHeader className="border-gray-200 border-b py-2! font-bold text-xl dark:border-gray-800">
         <CardTitle className="flex items-center">
-          <span className="flex-1">Alerts</span>
+          <span className="flex-1">
+            Alerts
+            {unreadCount > 0 && (
+              <span className="ml-2 font-normal text-muted-foreground text-sm">
+                {unreadCount} unread
+              </span>
+            )}
+          </span>
+          <Tooltip>
+            <TooltipTrigger asChild>
+              <Button
+                className="mr-2"
+                disabled={unreadCount === 0 || markAllAlertsRead.isPending}
+                onClick={() => markAllAlertsRead.mutate()}
+                size="icon"
+                variant="outline"
+              >
+                <CheckCheckIcon className="size-4" />
+              </Button>
+            </TooltipTrigger>
+            <TooltipContent>
+              <p>Mark all as read</p>
+            </TooltipContent>
+          </Tooltip>
           <Tooltip>
             <TooltipTrigger asChild>
               <Button
@@ -176,6 +212,10 @@ const AlertPanel = ({
   alert: Alert;
   className?: string;
 }) => {
+  const acknowledgeAlert = useAcknowledgeAlert();
+  const markAlertRead = useMarkAlertRead();
+  const isAcknowledged = Number(alert.ackTime) > 0;
+
   return (
     <div className={cn("flex gap-2", className)}>
       <div className="flex-0">
@@ -184,12 +224,67 @@ const AlertPanel = ({
       <div className="flex-1 overflow-x-hidden">
         <div className="mb-2 flex flex-col gap-1">
           <div className="flex flex-1 items-center gap-2">
-            <div className="nowrap flex-1 font-semibold text-md">
+            <div
+              className={cn(
+                "nowrap flex-1 text-md",
+                alert.read ? "font-medium" : "font-semibold"
+              )}
+            >
               {alert.subject}
             </div>
+            <Tooltip>
+              <TooltipTrigger asChild>
+                <Button
+                  disabled={markAlertRead.isPending}
+                  onClick={() =>
+                    markAlertRead.mutate({
+                      server: alert.server,
+                      alertId: alert.id,
+                      read: !alert.read,
+                    })
+                  }
+                  size="icon"
+                  variant="ghost"
+                >
+                  <CircleIcon
+                    className={cn(
+                      "size-2",
+                      alert.read
+                        ? "text-muted-foreground"
+                        : "fill-current text-indigo-9"
+                    )}
+                  />
+                </Button>
+              </TooltipTrigger>
+              <TooltipContent>
+                <p>{alert.read ? "Mark as unread" : "Mark as read"}</p>
+              </TooltipContent>
+            </Tooltip>
             <div className="flex-0 text-nowrap text-muted-foreground text-xs">
               {formatTimeAgo(new Date(alert.ctime), true)}
             </div>
+            {!(alert.fixed || isAcknowledged) && (
+              <Tooltip>
+                <TooltipTrigger asChild>
+                  <Button
+                    disabled={acknowledgeAlert.isPending}
+                    onClick={() =>
+                      acknowledgeAlert.mutate({
+                        server: alert.server,
+                        alertId: alert.id,
+                      })
+                    }
+                    size="icon"
+                    variant="ghost"
+                  >
+                    <CheckIcon className="size-4" />
+                  </Button>
+                </TooltipTrigger>
+                <TooltipContent>
+                  <p>Acknowledge</p>
+                </TooltipContent>
+              </Tooltip>
+            )}
           </div>
           <div className="text-muted-foreground text-xs">
             {formatDateTime(alert.ctime)} on {alert.trigger}
@@ -2,12 +2,16 @@ import { listen } from "@tauri-apps/api/event";
 import {
   AlertCircleIcon,
   BanIcon,
+  CheckCheckIcon,
   CheckCircleIcon,
+  CircleIcon,
   CircleStarIcon,
   CircleXIcon,
   ClockAlertIcon,
   Loader2Icon,
   RefreshCcwIcon,
+  RotateCwIcon,
+  SquareIcon,
 } from "lucide-react";
 import { useEffect, useRef } from "react";
 import { useLocation } from "react-router-dom";
@@ -29,7 +33,14 @@ import type { Build } from "@/lib/bindings";
 import { logger } from "@/lib/logger";
 import { formatDuration, formatTimeAgo } from "@/lib/time";
 import { cn } from "@/lib/utils";
-import { useBuilds } from "@/services/builds";
+import {
+  useBuilds,
+  useCancelBuild,
+  useMarkAllBuildsRead,
+  useMarkBuildRead,
+  usePollNow,
+  useRebuild,
+} from "@/services/builds";
 
 export const BuildsView = () => {
   // const navigate = useNavigate();
@@ -37,6 +48,9 @@ export const BuildsView = () => {
   const prevLocationRef = useRef<string | null>(null);
 
   const { data, isLoading, isError, error, refetch } = useBuilds();
+  const markAllBuildsRead = useMarkAllBuildsRead();
+  const pollNow = usePollNow();
+  const unreadCount = data?.unreadCount ?? 0;
 
   // Refetch data when route changes to this page
   useEffect(() => {
@@ -150,22 +164,50 @@ export const BuildsView = () => {
     <Card className="m-0 gap-0 rounded-none border-none py-0">
       <CardHeader className="border-gray-200 border-b py-2! font-bold text-xl dark:border-gray-800">
         <CardTitle className="flex items-center">
-          <span className="flex-1">Builds</span>
+          <span className="flex-1">
+            Builds
+            {unreadCount > 0 && (
+              <span className="ml-2 font-normal text-muted-foreground text-sm">
+                {unreadCount} unread
+              </span>
+            )}
+          </span>
+          <Tooltip>
+            <TooltipTrigger asChild>
+              <Button
+                className="mr-2"
+                disabled={unreadCount === 0 || markAllBuildsRead.isPending}
+                onClick={() => markAllBuildsRead.mutate()}
+                size="icon"
+                variant="outline"
+              >
+                <CheckCheckIcon className="size-4" />
+              </Button>
+            </TooltipTrigger>
+            <TooltipContent>
+              <p>Mark all as read</p>
+            </TooltipContent>
+          </Tooltip>
           <Tooltip>
             <TooltipTrigger asChild>
               <Button
-                disabled={isLoading}
-                onClick={() => refetch()}
+                disabled={isLoading || pollNow.isPending}
+                onClick={() =>
+                  pollNow.mutate(undefined, { onSettled: () => refetch() })
+                }
                 size="icon"
                 variant="outline"
               >
                 <RefreshCcwIcon
-                  className={cn("size-4", isLoading ? "animate-spin" : "")}
+                  className={cn(
+                    "size-4",
+                    isLoading || pollNow.isPending ? "animate-spin" : ""
+                  )}
                 />
               </Button>
             </TooltipTrigger>
             <TooltipContent>
-              <p>Refresh builds</p>
+              <p>Refresh now</p>
             </TooltipContent>
           </Tooltip>
         </CardTitle>
@@ -249,6 +291,9 @@ export const BuildPanel = ({
 }) => {
   const statusIcon = getBuildStatusIcon(build.status);
   const title = getBuildStatusTitle(build);
+  const rebuild = useRebuild();
+  const cancelBuild = useCancelBuild();
+  const markBuildRead = useMarkBuildRead();
 
   return (
     <div className={cn("flex gap-2", className)}>
@@ -256,12 +301,89 @@ export const BuildPanel = ({
       <div className="flex-1">
         <div className="mb-3 flex w-full items-center">
           <div className="flex flex-1 items-center gap-2">
-            <span className="flex-1 truncate text-nowrap font-semibold text-md text-overflow-ellipsis">
+            <span
+              className={cn(
+                "flex-1 truncate text-nowrap text-md text-overflow-ellipsis",
+                build.read ? "font-medium" : "font-semibold"
+              )}
+            >
               {title}
             </span>
+            <Tooltip>
+              <TooltipTrigger asChild>
+                <Button
+                  disabled={markBuildRead.isPending}
+                  onClick={() =>
+                    markBuildRead.mutate({
+                      server: build.server,
+                      buildId: build.id,
+                      read: !build.read,
+                    })
+                  }
+                  size="icon"
+                  variant="ghost"
+                >
+                  <CircleIcon
+                    className={cn(
+                      "size-2",
+                      build.read
+                        ? "text-muted-foreground"
+                        : "fill-current text-indigo-9"
+                    )}
+                  />
+                </Button>
+              </TooltipTrigger>
+              <TooltipContent>
+                <p>{build.read ? "Mark as unread" : "Mark as read"}</p>
+              </TooltipContent>
+            </Tooltip>
             <span className="text-nowrap text-muted-foreground text-xs">
               #{build.id}
             </span>
+            {build.status === "RUNNING" ? (
+              <Tooltip>
+                <TooltipTrigger asChild>
+                  <Button
+                    disabled={cancelBuild.isPending}
+                    onClick={() =>
+                      cancelBuild.mutate({
+                        server: build.server,
+                        buildId: build.id,
+                      })
+                    }
+                    size="icon"
+                    variant="ghost"
+                  >
+                    <SquareIcon className="size-4" />
+                  </Button>
+                </TooltipTrigger>
+                <TooltipContent>
+                  <p>Cancel build</p>
+                </TooltipContent>
+              </Tooltip>
+            ) : (
+              <Tooltip>
+                <TooltipTrigger asChild>
+                  <Button
+                    disabled={rebuild.isPending}
+                    onClick={() =>
+                      rebuild.mutate({
+                        server: build.server,
+                        buildId: build.id,
+                        configurationId: build.configuration,
+                      })
+                    }
+                    size="icon"
+                    variant="ghost"
+                  >
+                    <RotateCwIcon className="size-4" />
+                  </Button>
+                </TooltipTrigger>
+                <TooltipContent>
+                  <p>Rebuild</p>
+                </TooltipContent>
+              </Tooltip>
+            )}
           </div>
         </div>
         <div className="flex w-full gap-2">
@@ -2,16 +2,29 @@
 
 import { zodResolver } from "@hookform/resolvers/zod";
 import { invoke } from "@tauri-apps/api/core";
-import { fetch } from "@tauri-apps/plugin-http";
-import { Controller, useForm } from "react-hook-form";
+import { PlusIcon, Trash2Icon } from "lucide-react";
+import { useState } from "react";
+import {
+  type Control,
+  Controller,
+  useFieldArray,
+  useForm,
+} from "react-hook-form";
+import { toast } from "sonner";
 import { z } from "zod";
+import { NotificationRules } from "@/components/settings/notification-rules";
+import { QuietHours } from "@/components/settings/quiet-hours";
+import { WatchList } from "@/components/settings/watch-list";
 import { Button } from "@/components/ui/button";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
 import {
   Field,
+  FieldDescription,
   FieldError,
   FieldGroup,
   FieldLabel,
+  FieldLegend,
+  FieldSet,
 } from "@/components/ui/field";
 import { Input } from "@/components/ui/input";
 import {
@@ -24,17 +37,25 @@ import {
 import { Spinner } from "@/components/ui/spinner";
 import { Switch } from "@/components/ui/switch";
 import { useTheme } from "@/hooks/use-theme";
+import type {
+  ConnectionCheck,
+  ConnectionDiagnostic,
+  ServerProfile,
+} from "@/lib/bindings";
 import { logger } from "@/lib/logger";
-import { usePreferences, useSavePreferences } from "@/services/preferences";
+import {
+  usePreferences,
+  useSavePreferences,
+  useTestConnection,
+} from "@/services/preferences";
+import { type AppPreferences, REDACTED_TOKEN } from "@/types/preferences";
 
-const formSchema = z.object({
-  theme: z.enum(["system", "light", "dark"]),
-  enable_notifications: z.boolean(),
-  paused: z.boolean(),
-  notifications_total: z
-    .number()
-    .min(1)
-    .max(1000, "Notifications total must be between 1 and 1000"),
+const serverSchema = z.object({
+  name: z
+    .string()
+    .trim()
+    .min(1, "Name is required")
+    .max(50, "Name must be less than 50 characters"),
   server_url: z.url("Server URL must be a valid HTTP URL"),
   user: z
     .string()
@@ -48,6 +69,35 @@ const formSchema = z.object({
     .string()
     .min(1, "Token is required")
     .max(256, "Token must be less than 256 characters"),
+  enabled: z.boolean(),
+});
+
+const formSchema = z.object({
+  theme: z.enum(["system", "light", "dark"]),
+  enable_notifications: z.boolean(),
+  paused: z.boolean(),
+  notifications_total: z.coerce
+    .number<number>()
+    .int()
+    .min(1, "History size must be between 1 and 1000")
+    .max(1000, "History size must be between 1 and 1000"),
+  history_max_age_days: z.coerce
+    .number<number>()
+    .int()
+    .min(0, "History age must be between 0 and 36500 days")
+    .max(36_500, "History age must be between 0 and 36500 days"),
+  digest_window_in_mins: z.coerce
+    .number<number>()
+    .int()
+    .min(0, "Digest window must be 0 or more minutes"),
+  servers: z
+    .array(serverSchema)
+    .min(1, "At least one server is required")
+    .refine(
+      (servers) =>
+        new Set(servers.map((server) => server.name)).size === servers.length,
+      "Server names must be unique"
+    ),
   poll_interval_in_secs: z.coerce
     .number<number>()
     .int()
@@ -56,64 +106,157 @@ const formSchema = z.object({
 
 type SettingsFormValues = z.infer<typeof formSchema>;
 
+// Outcome of the connection test of a server, with the error instead when the
+// test could not run
+type ConnectionTest = {
+  diagnostic?: ConnectionDiagnostic;
+  error?: string;
+};
+
+// Profiles of the servers to edit, the single server of the settings saved
+// before several servers were supported becomes the default profile
+const getServerProfiles = (
+  preferences: AppPreferences | undefined
+): ServerProfile[] => {
+  if (preferences?.servers?.length) {
+    return preferences.servers;
+  }
+  return [
+    {
+      name: DEFAULT_SERVER_NAME,
+      server_url: preferences?.server_url ?? "",
+      user: preferences?.user ?? "",
+      token: preferences?.token ?? "",
+      enabled: true,
+    },
+  ];
+};
+
 export const SettingsView = () => {
   const { data: preferences } = usePreferences();
   const savePreferences = useSavePreferences();
+  const testConnection = useTestConnection();
+  // Last connection tests, by the id of the server field
+  const [connectionTests, setConnectionTests] = useState<
+    Record<string, ConnectionTest>
+  >({});
 
   const form = useForm<SettingsFormValues>({
     resolver: zodResolver(formSchema),
-    defaultValues: preferences,
+    defaultValues: {
+      ...preferences,
+      servers: getServerProfiles(preferences).map((server) => ({
+        name: server.name,
+        server_url: server.server_url ?? "",
+        user: server.user ?? "",
+        token: server.token ?? "",
+        enabled: server.enabled ?? true,
+      })),
+    },
   });
+  const servers = useFieldArray({ control: form.control, name: "servers" });
+
+  // Checks the server URL and the credentials of a server of the form, the
+  // failed checks are warnings as the settings can be saved anyway
+  const checkConnection = async (index: number): Promise<boolean> => {
+    const fieldId = servers.fields[index].id;
+    const server = form.getValues(`servers.${index}`);
+    let test: ConnectionTest;
+    try {
+      test = {
+        diagnostic: await testConnection.mutateAsync({
+          ...server,
+          name: server.name.trim(),
+          server_url: server.server_url.trim(),
+        }),
+      };
+    } catch (error) {
+      test = {
+        error: error instanceof Error ? error.message : String(error),
+      };
+    }
+    setConnectionTests((others) => ({ ...others, [fieldId]: test }));
+    return test.diagnostic?.ok ?? false;
+  };
+
+  const onTestConnection = async (index: number) => {
+    const valid = await form.trigger([
+      `servers.${index}.name`,
+      `servers.${index}.server_url`,
+      `servers.${index}.user`,
+      `servers.${index}.token`,
+    ]);
+    if (valid) {
+      await checkConnection(index);
+    }
+  };
+
+  const onAddServer = () => {
+    servers.append({
+      name: "",
+      server_url: "",
+      user: "",
+      token: "",
+      enabled: true,
+    });
+  };
+
+  // A redacted token stands for the token saved under the name of the server,
+  // it has to be entered again once the server is renamed
+  const onRenameServer = (index: number) => {
+    if (form.getValues(`servers.${index}.token`) === REDACTED_TOKEN) {
+      form.setValue(`servers.${index}.token`, "");
+    }
+  };
+
+  const onRemoveServer = (index: number) => {
+    const fieldId = servers.fields[index].id;
+    setConnectionTests(({ [fieldId]: _, ...others }) => others);
+    servers.remove(index);
+  };
 
   const { theme, setTheme } = useTheme();
 
   const onSubmit = async (data: SettingsFormValues) => {
-    // Validate server URL by calling the version API
-    const trimmedServerUrl = data.server_url.trim();
-    logger.info("Validating server URL", {
-      trimmedServerUrl,
-      user: data.user,
-      token: data.token,
-    });
-    const statusCode = await validateServerUrl(
-      trimmedServerUrl,
-      data.user,
-      data.token
+    const results = await Promise.all(
+      data.servers
+        .map((server, index) => ({ server, index }))
+        .filter(({ server }) => server.enabled)
+        .map(({ index }) => checkConnection(index))
     );
-    logger.info("Server URL validated", { statusCode });
-    if (statusCode === 503) {
-      form.setError("server_url", {
-        type: "manual",
-        message:
-          "Unable to connect to the server. Please check the server URL and try again.",
-      });
-      return;
-    }
-    if (statusCode === 401) {
-      form.setError("user", {
-        type: "manual",
-        message:
-          "Invalid token or password. Please check the token and try again.",
-      });
-      return;
-    }
-    if (statusCode >= 400) {
-      form.setError("server_url", {
-        type: "manual",
-        message: `Server returned status ${statusCode}. Please check the server URL and try again.`,
-      });
-      return;
-    }
 
-    logger.debug("Submitting settings", { data });
+    logger.debug("Submitting settings", {
+      data: {
+        ...data,
+        servers: data.servers.map((server) => ({
+          ...server,
+          token: REDACTED_TOKEN,
+        })),
+      },
+    });
+    // Settings edited elsewhere, like the notification rules or the watch list,
+    // are not part of the form and are kept as saved by the backend
     savePreferences.mutate({
+      ...preferences,
       ...data,
       theme,
       enable_notifications: data.enable_notifications,
       paused: data.paused,
-      server_url: trimmedServerUrl,
+      paused_until: data.paused ? preferences?.paused_until : null,
+      servers: data.servers.map((server) => ({
+        ...server,
+        server_url: server.server_url.trim(),
+      })),
     });
 
+    // The window stays open to show the servers failing the connection test
+    if (results.some((ok) => !ok)) {
+      toast.warning("Settings saved", {
+        description: "Some servers failed the connection test",
+      });
+      return;
+    }
+
     try {
       await invoke("close_main_window");
     } catch (error) {
@@ -136,7 +279,7 @@ export const SettingsView = () => {
       <CardHeader className="border-gray-200 border-b py-2! font-bold text-xl dark:border-gray-800">
         <CardTitle>Preferences</CardTitle>
       </CardHeader>
-      <CardContent className="p-6">
+      <CardContent className="space-y-8 p-6">
         <form
           className="space-y-8"
           id="form-settings"
@@ -218,23 +361,56 @@ export const SettingsView = () => {
                 </Field>
               )}
             />
+            <FieldSet className="gap-4">
+              <FieldLegend className="mb-0 font-semibold" variant="label">
+                QuickBuild Servers
+              </FieldLegend>
+              {servers.fields.map((field, index) => (
+                <ServerProfileFields
+                  connectionTest={connectionTests[field.id]}
+                  control={form.control}
+                  index={index}
+                  key={field.id}
+                  onRemove={
+                    servers.fields.length > 1
+                      ? () => onRemoveServer(index)
+                      : undefined
+                  }
+                  onRename={() => onRenameServer(index)}
+                  onTestConnection={() => onTestConnection(index)}
+                  testing={
+                    testConnection.isPending || form.formState.isSubmitting
+                  }
+                />
+              ))}
+              <FieldError
+                errors={[
+                  form.formState.errors.servers?.root ??
+                    form.formState.errors.servers,
+                ]}
+              />
+              <Button onClick={onAddServer} type="button" variant="outline">
+                <PlusIcon className="size-4" />
+                Add Server
+              </Button>
+            </FieldSet>
             <Controller
               control={form.control}
-              name="server_url"
+              name="poll_interval_in_secs"
               render={({ field, fieldState }) => (
                 <Field data-invalid={fieldState.invalid}>
                   <FieldLabel
                     className="font-semibold"
-                    htmlFor="form-settings-server-url"
+                    htmlFor="form-settings-poll-interval-in-secs"
                   >
-                    QuickBuild Server
+                    Poll Interval (seconds)
                   </FieldLabel>
                   <Input
                     {...field}
                     aria-invalid={fieldState.invalid}
                     autoComplete="off"
-                    id="form-settings-server-url"
-                    placeholder="http://quickbuild:8810"
+                    id="form-settings-poll-interval-in-secs"
+                    placeholder="10"
                   />
                   {fieldState.invalid && (
                     <FieldError errors={[fieldState.error]} />
@@ -244,21 +420,21 @@ export const SettingsView = () => {
             />
             <Controller
               control={form.control}
-              name="user"
+              name="notifications_total"
               render={({ field, fieldState }) => (
                 <Field data-invalid={fieldState.invalid}>
                   <FieldLabel
                     className="font-semibold"
-                    htmlFor="form-settings-user"
+                    htmlFor="form-settings-notifications-total"
                   >
-                    User
+                    History Size (builds and alerts)
                   </FieldLabel>
                   <Input
                     {...field}
                     aria-invalid={fieldState.invalid}
                     autoComplete="off"
-                    id="form-settings-user"
-                    placeholder="username"
+                    id="form-settings-notifications-total"
+                    placeholder="100"
                   />
                   {fieldState.invalid && (
                     <FieldError errors={[fieldState.error]} />
@@ -268,22 +444,21 @@ export const SettingsView = () => {
             />
             <Controller
               control={form.control}
-              name="token"
+              name="history_max_age_days"
               render={({ field, fieldState }) => (
                 <Field data-invalid={fieldState.invalid}>
                   <FieldLabel
                     className="font-semibold"
-                    htmlFor="form-settings-token"
+                    htmlFor="form-settings-history-max-age-days"
                   >
-                    Token/Password
+                    History Age (days, 0 keeps all)
                   </FieldLabel>
                   <Input
                     {...field}
                     aria-invalid={fieldState.invalid}
                     autoComplete="off"
-                    id="form-settings-token"
-                    placeholder="token or password"
-                    type="password"
+                    id="form-settings-history-max-age-days"
+                    placeholder="0"
                   />
                   {fieldState.invalid && (
                     <FieldError errors={[fieldState.error]} />
@@ -293,21 +468,21 @@ export const SettingsView = () => {
             />
             <Controller
               control={form.control}
-              name="poll_interval_in_secs"
+              name="digest_window_in_mins"
               render={({ field, fieldState }) => (
                 <Field data-invalid={fieldState.invalid}>
                   <FieldLabel
                     className="font-semibold"
-                    htmlFor="form-settings-poll-interval-in-secs"
+                    htmlFor="form-settings-digest-window-in-mins"
                   >
-                    Poll Interval (seconds)
+                    Digest Window (minutes, 0 notifies right away)
                   </FieldLabel>
                   <Input
                     {...field}
                     aria-invalid={fieldState.invalid}
                     autoComplete="off"
-                    id="form-settings-poll-interval-in-secs"
-                    placeholder="10"
+                    id="form-settings-digest-window-in-mins"
+                    placeholder="0"
                   />
                   {fieldState.invalid && (
                     <FieldError errors={[fieldState.error]} />
@@ -318,7 +493,7 @@ export const SettingsView = () => {
           </FieldGroup>
           <Field className="justify-end" orientation="horizontal">
             <Button
-              className="w-full"
+              className="flex-1"
               disabled={form.formState.isSubmitting}
               form="form-settings"
               type="submit"
@@ -330,48 +505,210 @@ export const SettingsView = () => {
             </Button>
           </Field>
         </form>
+        <WatchList />
+        <NotificationRules />
+        <QuietHours />
       </CardContent>
     </Card>
   );
 };
 
-export const validateServerUrl = async (
-  serverUrl: string,
-  user: string,
-  token: string
-): Promise<number> => {
-  const versionUrl = `${serverUrl}/rest/version`;
-  logger.info("Validating server URL", { versionUrl, user, token });
-
-  const timeoutMs = 20_000; // 20 seconds timeout
+const DEFAULT_SERVER_NAME = "default";
 
-  try {
-    const timeoutPromise = new Promise<number>((_, reject) => {
-      setTimeout(() => {
-        logger.error("Request timeout after 20 seconds");
-        reject(new Error("Request timeout after 20 seconds"));
-      }, timeoutMs);
-    });
+const ServerProfileFields = ({
+  control,
+  index,
+  connectionTest,
+  testing,
+  onTestConnection,
+  onRemove,
+  onRename,
+}: {
+  control: Control<SettingsFormValues>;
+  index: number;
+  connectionTest?: ConnectionTest;
+  testing: boolean;
+  onTestConnection: () => void;
+  // Missing for the last server, which cannot be removed
+  onRemove?: () => void;
+  onRename: () => void;
+}) => {
+  const id = `form-settings-servers-${index}`;
+  return (
+    <FieldGroup className="gap-4 rounded-md border p-4">
+      <div className="flex items-center space-x-2">
+        <Controller
+          control={control}
+          name={`servers.${index}.enabled`}
+          render={({ field }) => (
+            <Field className="mr-auto">
+              <div className="flex items-center space-x-2">
+                <Switch
+                  checked={Boolean(field.value)}
+                  id={`${id}-enabled`}
+                  onCheckedChange={field.onChange}
+                  value={field.value ? "true" : "false"}
+                />
+                <FieldLabel
+                  className="flex-1 font-semibold"
+                  htmlFor={`${id}-enabled`}
+                >
+                  Enabled
+                </FieldLabel>
+              </div>
+            </Field>
+          )}
+        />
+        {onRemove && (
+          <Button
+            onClick={onRemove}
+            size="icon"
+            title="Remove server"
+            type="button"
+            variant="ghost"
+          >
+            <Trash2Icon className="size-4" />
+          </Button>
+        )}
+      </div>
+      <Controller
+        control={control}
+        name={`servers.${index}.name`}
+        render={({ field, fieldState }) => (
+          <Field data-invalid={fieldState.invalid}>
+            <FieldLabel className="font-semibold" htmlFor={`${id}-name`}>
+              Name
+            </FieldLabel>
+            <Input
+              {...field}
+              aria-invalid={fieldState.invalid}
+              autoComplete="off"
+              id={`${id}-name`}
+              onChange={(event) => {
+                field.onChange(event);
+                onRename();
+              }}
+              placeholder="default"
+            />
+            {fieldState.invalid && <FieldError errors={[fieldState.error]} />}
+          </Field>
+        )}
+      />
+      <Controller
+        control={control}
+        name={`servers.${index}.server_url`}
+        render={({ field, fieldState }) => (
+          <Field data-invalid={fieldState.invalid}>
+            <FieldLabel className="font-semibold" htmlFor={`${id}-server-url`}>
+              QuickBuild Server
+            </FieldLabel>
+            <Input
+              {...field}
+              aria-invalid={fieldState.invalid}
+              autoComplete="off"
+              id={`${id}-server-url`}
+              placeholder="http://quickbuild:8810"
+            />
+            {fieldState.invalid && <FieldError errors={[fieldState.error]} />}
+          </Field>
+        )}
+      />
+      <Controller
+        control={control}
+        name={`servers.${index}.user`}
+        render={({ field, fieldState }) => (
+          <Field data-invalid={fieldState.invalid}>
+            <FieldLabel className="font-semibold" htmlFor={`${id}-user`}>
+              User
+            </FieldLabel>
+            <Input
+              {...field}
+              aria-invalid={fieldState.invalid}
+              autoComplete="off"
+              id={`${id}-user`}
+              placeholder="username"
+            />
+            {fieldState.invalid && <FieldError errors={[fieldState.error]} />}
+          </Field>
+        )}
+      />
+      <Controller
+        control={control}
+        name={`servers.${index}.token`}
+        render={({ field, fieldState }) => (
+          <Field data-invalid={fieldState.invalid}>
+            <FieldLabel className="font-semibold" htmlFor={`${id}-token`}>
+              Token/Password
+            </FieldLabel>
+            <Input
+              {...field}
+              aria-invalid={fieldState.invalid}
+              autoComplete="off"
+              id={`${id}-token`}
+              placeholder="token or password"
+              type="password"
+            />
+            <FieldDescription>
+              Kept in the OS keyring. Without keyring, it is encrypted in the
+              app config directory with a key stored next to it, which only
+              keeps it out of the settings and their backups.
+            </FieldDescription>
+            {fieldState.invalid && <FieldError errors={[fieldState.error]} />}
+          </Field>
+        )}
+      />
+      {connectionTest && <ConnectionTestView test={connectionTest} />}
+      <Field className="justify-end" orientation="horizontal">
+        <Button
+          disabled={testing}
+          onClick={onTestConnection}
+          type="button"
+          variant="outline"
+        >
+          Test Connection
+        </Button>
+      </Field>
+    </FieldGroup>
+  );
+};
 
-    const fetchPromise = fetch(versionUrl, {
-      method: "GET",
-      headers: {
-        Authorization: `Basic ${btoa(`${user}:${token}`)}`,
-      },
-    })
-      .then((response) => {
-        logger.debug("Response status", { status: response.status });
-        return response.status;
-      })
-      .catch((error) => {
-        logger.error("Failed to validate server URL", { error });
-        return 503;
-      });
+const CHECK_LABELS: [keyof ConnectionDiagnostic, string][] = [
+  ["reachable", "Server reachable"],
+  ["authenticated", "Credentials accepted"],
+  ["notificationsEndpoint", "Notifications API available"],
+  ["version", "Server version"],
+];
 
-    const statusCode = await Promise.race([fetchPromise, timeoutPromise]);
-    return statusCode;
-  } catch (error) {
-    logger.error("Failed to validate server URL", { error });
-    return 503;
+const ConnectionTestView = ({ test }: { test: ConnectionTest }) => {
+  if (!test.diagnostic) {
+    return <p className="text-amber-9 text-sm">{test.error}</p>;
   }
+
+  const diagnostic = test.diagnostic;
+  return (
+    <ul className="space-y-1 text-sm">
+      {CHECK_LABELS.map(([key, label]) => {
+        const check = diagnostic[key] as ConnectionCheck;
+        return (
+          <li key={key}>
+            <div className="flex justify-between gap-2">
+              <span>{label}</span>
+              <span
+                className={
+                  check.status === "failed"
+                    ? "text-amber-9"
+                    : "text-muted-foreground"
+                }
+              >
+                {key === "version" && check.status === "passed"
+                  ? diagnostic.serverVersion
+                  : check.status}
+              </span>
+            </div>
+            {check.error && <p className="text-amber-9">{check.error}</p>}
+          </li>
+        );
+      })}
+    </ul>
+  );
 };